@@ -32,9 +32,12 @@ use crate::bellman::plonk::better_better_cs::cs::{
 
 use num_bigint::BigUint;
 
+use crate::plonk::circuit::Assignment;
+
 use super::super::allocated_num::{AllocatedNum, Num};
 use super::super::linear_combination::LinearCombination;
 use super::super::simple_term::Term;
+use super::super::boolean::Boolean;
 
 use super::constraint_num_bits;
 
@@ -202,6 +205,170 @@ impl<E: Engine> Limb<E> {
             false
         }
     }
+
+    // limb needs reduction once its max value may no longer fit into the intermediate capacity of
+    // the representation, which is never larger than the field capacity: anything up to E::Fr::CAPACITY bits
+    // is represented by a field element uniquely, while anything above can silently wrap around the modulus
+    pub fn needs_reduction(&self, params: &LimbedRepresentationParameters<E>) -> bool {
+        let capacity = std::cmp::min(params.limb_intermediate_value_capacity, E::Fr::CAPACITY as usize);
+
+        self.max_value.bits() as usize > capacity
+    }
+
+    // range checks the limb to the limb width of the representation and resets max value accordingly
+    pub fn enforce_reduced<CS: ConstraintSystem<E>>(
+        &mut self,
+        cs: &mut CS,
+        params: &LimbedRepresentationParameters<E>
+    ) -> Result<(), SynthesisError> {
+        use super::single_table_range_constraint::enforce_using_single_column_table_for_shifted_variable_optimized;
+
+        let num = self.collapse_into_num(cs)?;
+        match &num {
+            Num::Constant(c) => {
+                assert!(fe_to_biguint(c) <= params.limb_max_value, "constant limb doesn't fit into the limb width");
+            },
+            Num::Variable(var) => {
+                enforce_using_single_column_table_for_shifted_variable_optimized(cs, var, E::Fr::one(), params.limb_size_bits)?;
+            }
+        }
+
+        self.term = Term::from_num(num);
+        self.max_value = params.limb_max_value.clone();
+
+        Ok(())
+    }
+
+    // splits the limb into range checked sub-limbs of the given width and enforces that they recompose
+    // into the original term. The top sub-limb is only as wide as what is left of the max value bits.
+    // If max value has already overflown the field we can only split the field value, so the number of
+    // sub-limbs is bounded by the field bit width, and as such sub-limbs can hold both x and x + p,
+    // the recomposed value is also enforced to be less than the field modulus
+    pub fn split_into_range_checked_limbs<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        sub_limb_width: usize
+    ) -> Result<Vec<Self>, SynthesisError> {
+        assert!(sub_limb_width > 0);
+        let max_bits = std::cmp::min(self.max_value.bits() as usize, E::Fr::NUM_BITS as usize);
+        let max_bits = std::cmp::max(max_bits, 1);
+        let num_sub_limbs = (max_bits + sub_limb_width - 1) / sub_limb_width;
+
+        if self.is_constant() {
+            let value = fe_to_biguint(&self.collapse_into_constant());
+            let sub_limbs = split_into_fixed_number_of_limbs(value, sub_limb_width, num_sub_limbs);
+
+            return Ok(sub_limbs.into_iter().map(|el| Self::new_constant(el)).collect());
+        }
+
+        let values = split_some_into_fixed_number_of_limbs(self.get_value(), sub_limb_width, num_sub_limbs);
+
+        self.split_into_range_checked_limbs_for_witness(cs, sub_limb_width, values)
+    }
+
+    fn split_into_range_checked_limbs_for_witness<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        sub_limb_width: usize,
+        values: Vec<Option<BigUint>>
+    ) -> Result<Vec<Self>, SynthesisError> {
+        use super::single_table_range_constraint::enforce_using_single_column_table_for_shifted_variable_optimized;
+
+        let max_bits = std::cmp::min(self.max_value.bits() as usize, E::Fr::NUM_BITS as usize);
+        let max_bits = std::cmp::max(max_bits, 1);
+        let num_sub_limbs = values.len();
+        let top_sub_limb_width = max_bits - (num_sub_limbs - 1) * sub_limb_width;
+
+        let mut lc = LinearCombination::zero();
+        let mut term = self.term.clone();
+        term.negate();
+        lc.add_assign_term(&term);
+
+        let shift = biguint_to_fe::<E::Fr>(BigUint::from(1u64) << sub_limb_width);
+        let mut coeff = E::Fr::one();
+
+        let mut sub_limbs = Vec::with_capacity(num_sub_limbs);
+        for (i, value) in values.into_iter().enumerate() {
+            let width = if i == num_sub_limbs - 1 { top_sub_limb_width } else { sub_limb_width };
+            let fe = some_biguint_to_fe::<E::Fr>(&value);
+            let var = AllocatedNum::alloc(cs, || Ok(*fe.get()?))?;
+            enforce_using_single_column_table_for_shifted_variable_optimized(cs, &var, E::Fr::one(), width)?;
+
+            lc.add_assign_variable_with_coeff(&var, coeff);
+            coeff.mul_assign(&shift);
+
+            let max_value = (BigUint::from(1u64) << width) - BigUint::from(1u64);
+            sub_limbs.push(Self::new(Term::from_allocated_num(var), max_value));
+        }
+        lc.enforce_zero(cs)?;
+
+        if max_bits == E::Fr::NUM_BITS as usize {
+            Self::enforce_less_than_modulus(cs, &sub_limbs, sub_limb_width, top_sub_limb_width)?;
+        }
+
+        Ok(sub_limbs)
+    }
+
+    // enforces that range checked sub-limbs recompose into a value that is at most p - 1 by subtracting
+    // them from p - 1 with borrows. The top difference takes no borrow out, so it only fits into
+    // the top sub-limb width if the recomposed value is not larger than p - 1
+    fn enforce_less_than_modulus<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        sub_limbs: &[Self],
+        sub_limb_width: usize,
+        top_sub_limb_width: usize
+    ) -> Result<(), SynthesisError> {
+        use super::single_table_range_constraint::enforce_using_single_column_table_for_shifted_variable_optimized;
+
+        let num_sub_limbs = sub_limbs.len();
+        let modulus_minus_one = repr_to_biguint::<E::Fr>(&E::Fr::char()) - BigUint::from(1u64);
+        let bound_limbs = split_into_fixed_number_of_limbs(modulus_minus_one, sub_limb_width, num_sub_limbs);
+        let limb_modulus = BigUint::from(1u64) << sub_limb_width;
+        let shift = biguint_to_fe::<E::Fr>(limb_modulus.clone());
+
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+
+        let mut borrow = Boolean::constant(false);
+        for (i, (sub_limb, bound)) in sub_limbs.iter().zip(bound_limbs.into_iter()).enumerate() {
+            let is_top = i == num_sub_limbs - 1;
+            let bound_fe = biguint_to_fe::<E::Fr>(bound.clone());
+
+            let witness = match (sub_limb.get_value(), borrow.get_value()) {
+                (Some(x), Some(bw)) => {
+                    let subtrahend = x + BigUint::from(bw as u64);
+                    if is_top {
+                        let mut diff = bound_fe;
+                        diff.sub_assign(&biguint_to_fe::<E::Fr>(subtrahend));
+                        Some((diff, false))
+                    } else if bound >= subtrahend {
+                        Some((biguint_to_fe::<E::Fr>(bound - subtrahend), false))
+                    } else {
+                        Some((biguint_to_fe::<E::Fr>(bound + &limb_modulus - subtrahend), true))
+                    }
+                },
+                _ => None
+            };
+
+            let diff = AllocatedNum::alloc(cs, || Ok(witness.get()?.0))?;
+            let width = if is_top { top_sub_limb_width } else { sub_limb_width };
+            enforce_using_single_column_table_for_shifted_variable_optimized(cs, &diff, E::Fr::one(), width)?;
+
+            let mut lc = LinearCombination::zero();
+            lc.add_assign_constant(bound_fe);
+            lc.add_assign_term_with_coeff(&sub_limb.term, minus_one);
+            lc.add_assign_boolean_with_coeff(&borrow, minus_one);
+            lc.add_assign_variable_with_coeff(&diff, minus_one);
+            if !is_top {
+                let new_borrow = Boolean::alloc(cs, witness.map(|(_, bw)| bw))?;
+                lc.add_assign_boolean_with_coeff(&new_borrow, shift);
+                borrow = new_borrow;
+            }
+            lc.enforce_zero(cs)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub fn repr_to_biguint<F: PrimeField>(repr: &F::Repr) -> BigUint {
@@ -293,6 +460,23 @@ pub fn some_fe_to_biguint<F: PrimeField>(el: &Option<F>) -> Option<BigUint> {
     }
 }
 
+pub fn biguint_from_le_bytes(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_le(bytes)
+}
+
+// serializes into exactly `len` little-endian bytes, padding with zeroes
+#[track_caller]
+pub fn biguint_to_le_bytes(value: &BigUint, len: usize) -> Vec<u8> {
+    let mut bytes = value.to_bytes_le();
+    if value.bits() == 0 {
+        bytes.clear();
+    }
+    assert!(bytes.len() <= len, "value takes {} bytes, while only {} are available", bytes.len(), len);
+    bytes.resize(len, 0u8);
+
+    bytes
+}
+
 pub fn get_bit_slice(v: BigUint, start: usize, end: usize) -> BigUint {
     let mut tmp = v;
     tmp >>= start;
@@ -419,4 +603,93 @@ pub(crate) fn make_multiple(mut value: usize, multiple_of: usize) -> usize {
     }
 
     value
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::plonk::circuit::*;
+    use crate::plonk::circuit::tables::inscribe_default_range_table_for_bit_width_over_first_three_columns;
+    use crate::bellman::plonk::better_better_cs::cs::*;
+    use crate::bellman::pairing::bn256::{Bn256, Fr};
+
+    #[test]
+    fn test_split_overflown_limb_into_range_checked_limbs() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>::new();
+        inscribe_default_range_table_for_bit_width_over_first_three_columns(&mut cs, 16).unwrap();
+
+        let value: Fr = rng.gen();
+        let var = AllocatedNum::alloc(&mut cs, || Ok(value)).unwrap();
+        let mut limb = Limb::<Bn256>::new(Term::from_allocated_num(var), BigUint::from(1u64) << 64);
+        // pretend that we have accumulated way more than the field can hold
+        limb.scale_max(&(BigUint::from(1u64) << 300));
+        assert!(limb.max_value().bits() as usize > Fr::CAPACITY as usize);
+
+        let sub_limbs = limb.split_into_range_checked_limbs(&mut cs, 64).unwrap();
+        assert_eq!(sub_limbs.len(), 4);
+
+        // the top sub-limb only has what is left of the field bit width
+        let top_width = Fr::NUM_BITS as usize - 3 * 64;
+        assert_eq!(sub_limbs[3].max_value(), (BigUint::from(1u64) << top_width) - BigUint::from(1u64));
+
+        let mut recomposed = BigUint::from(0u64);
+        for sub_limb in sub_limbs.iter().rev() {
+            assert!(sub_limb.max_value() <= (BigUint::from(1u64) << 64) - BigUint::from(1u64));
+            recomposed <<= 64;
+            recomposed += sub_limb.get_value().unwrap();
+        }
+        assert_eq!(recomposed, fe_to_biguint(&value));
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_split_overflown_limb_rejects_aliased_sub_limbs() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>::new();
+        inscribe_default_range_table_for_bit_width_over_first_three_columns(&mut cs, 16).unwrap();
+
+        let value = Fr::from_str("1234567890").unwrap();
+        let var = AllocatedNum::alloc(&mut cs, || Ok(value)).unwrap();
+        let mut limb = Limb::<Bn256>::new(Term::from_allocated_num(var), BigUint::from(1u64) << 64);
+        limb.scale_max(&(BigUint::from(1u64) << 300));
+
+        // v + p still fits into the field bit width and recomposes into v in the field
+        let modulus = repr_to_biguint::<Fr>(&Fr::char());
+        let aliased = fe_to_biguint(&value) + modulus;
+        assert!(aliased.bits() as usize <= Fr::NUM_BITS as usize);
+        let values = split_some_into_fixed_number_of_limbs(Some(aliased), 16, 16);
+
+        limb.split_into_range_checked_limbs_for_witness(&mut cs, 16, values).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_limb_overflow_tracking() {
+        let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>::new();
+        inscribe_default_range_table_for_bit_width_over_first_three_columns(&mut cs, 16).unwrap();
+
+        let params = LimbedRepresentationParameters::<Bn256>::new(64, Fr::CAPACITY as usize);
+
+        let value = Fr::from_str("1234567890").unwrap();
+        let var = AllocatedNum::alloc(&mut cs, || Ok(value)).unwrap();
+        let mut limb = Limb::<Bn256>::new(Term::from_allocated_num(var), params.limb_max_value.clone());
+        assert!(!limb.needs_reduction(&params));
+
+        // exactly at the capacity is still fine
+        limb.max_value = (BigUint::from(1u64) << (Fr::CAPACITY as usize)) - BigUint::from(1u64);
+        assert!(!limb.needs_reduction(&params));
+
+        limb.inc_max(&BigUint::from(1u64));
+        assert!(limb.needs_reduction(&params));
+
+        limb.enforce_reduced(&mut cs, &params).unwrap();
+        assert!(!limb.needs_reduction(&params));
+        assert_eq!(limb.max_value(), params.limb_max_value);
+        assert_eq!(limb.get_field_value().unwrap(), value);
+
+        assert!(cs.is_satisfied());
+    }
+}
@@ -0,0 +1,3408 @@
+use crate::bellman::pairing::{
+    Engine,
+};
+
+use crate::bellman::pairing::ff::{
+    Field,
+    PrimeField,
+};
+
+use crate::bellman::{
+    SynthesisError,
+};
+
+use crate::bellman::plonk::better_better_cs::cs::{
+    ConstraintSystem,
+    MainGateTerm,
+    MainGate,
+    LookupTableApplication,
+};
+use crate::bellman::plonk::better_better_cs::data_structures::PolyIdentifier;
+use crate::bellman::plonk::better_better_cs::lookup_tables::*;
+
+use crate::plonk::circuit::Assignment;
+use super::bigint::*;
+use super::split_some_into_slices;
+use super::single_table_range_constraint::enforce_using_single_column_table_for_shifted_variable_optimized;
+
+use crate::plonk::circuit::allocated_num::{AllocatedNum, Num};
+use crate::plonk::circuit::boolean::Boolean;
+use crate::plonk::circuit::linear_combination::LinearCombination;
+use crate::plonk::circuit::utils::u64_to_fe;
+use crate::plonk::circuit::hashes_with_tables::get_or_create_table;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use std::sync::Arc;
+
+// helpers over little-endian arrays of 64-bit limbs represented as `Num<E>`,
+// every limb is expected to be range checked by the caller unless stated otherwise
+
+pub const LIMB_WIDTH: usize = 64;
+
+const NIBBLE_WIDTH: usize = 4;
+const NIBBLES_PER_LIMB: usize = LIMB_WIDTH / NIBBLE_WIDTH;
+
+const BYTE_WIDTH: usize = 8;
+const BYTES_PER_LIMB: usize = LIMB_WIDTH / BYTE_WIDTH;
+
+// enforces that limb is at most `width` bits long, constants are checked in place
+pub(crate) fn enforce_limb_width<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    limb: &Num<E>,
+    width: usize
+) -> Result<(), SynthesisError> {
+    match limb {
+        Num::Constant(c) => {
+            let bits = fe_to_biguint(c).bits() as usize;
+            assert!(bits <= width, "constant limb has {} bits, while expected at most {}", bits, width);
+        },
+        Num::Variable(var) => {
+            if width == 0 {
+                var.assert_is_zero(cs)?;
+            } else {
+                enforce_using_single_column_table_for_shifted_variable_optimized(cs, var, E::Fr::one(), width)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// enforces 0 <= el <= bound by range checking both `el` and `bound - el` to the width of the bound
+pub(crate) fn enforce_at_most_constant<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    el: &Num<E>,
+    bound: &BigUint
+) -> Result<(), SynthesisError> {
+    let width = bound.bits() as usize;
+    enforce_limb_width(cs, el, width)?;
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut lc = LinearCombination::zero();
+    lc.add_assign_constant(biguint_to_fe(bound.clone()));
+    lc.add_assign_number_with_coeff(el, minus_one);
+    let gap = lc.into_num(cs)?;
+    enforce_limb_width(cs, &gap, width)?;
+
+    Ok(())
+}
+
+pub fn get_limbs_value<E: Engine>(limbs: &[Num<E>]) -> Option<BigUint> {
+    let mut result = BigUint::from(0u64);
+    for limb in limbs.iter().rev() {
+        result <<= LIMB_WIDTH;
+        result += fe_to_biguint(&limb.get_value()?);
+    }
+
+    Some(result)
+}
+
+// nibbles come in the order of the Merkle-Patricia path, so most significant nibble goes first,
+// the result is a little-endian array of 64-bit limbs, sixteen nibbles per limb
+pub fn from_nibbles<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    nibbles: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert!(nibbles.len() > 0);
+
+    for nibble in nibbles.iter() {
+        enforce_limb_width(cs, nibble, NIBBLE_WIDTH)?;
+    }
+
+    let mut nibble_shift = E::Fr::one();
+    for _ in 0..NIBBLE_WIDTH {
+        nibble_shift.double();
+    }
+
+    let mut limbs = Vec::with_capacity((nibbles.len() + NIBBLES_PER_LIMB - 1) / NIBBLES_PER_LIMB);
+    let le_nibbles: Vec<_> = nibbles.iter().rev().cloned().collect();
+    for chunk in le_nibbles.chunks(NIBBLES_PER_LIMB) {
+        let mut lc = LinearCombination::zero();
+        let mut coeff = E::Fr::one();
+        for nibble in chunk.iter() {
+            lc.add_assign_number_with_coeff(nibble, coeff);
+            coeff.mul_assign(&nibble_shift);
+        }
+
+        limbs.push(lc.into_num(cs)?);
+    }
+
+    Ok(limbs)
+}
+
+// computes sum_k a_k * b_k + sum_j c_j over little-endian limbed numbers: all partial products and addends
+// are first accumulated into wide columns and then a single carry pass normalizes columns into 64-bit limbs.
+// Carries widths are derived from the maximum possible column values, so result has as many limbs
+// as needed to hold the maximum possible value of the expression
+pub fn mul_accumulate<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    muls: &[(Vec<Num<E>>, Vec<Num<E>>)],
+    adds: &[Vec<Num<E>>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let limb_max = (BigUint::from(1u64) << LIMB_WIDTH) - 1u64;
+
+    let mut max_total = BigUint::from(0u64);
+    let mut num_columns = 0;
+    for (a, b) in muls.iter() {
+        assert!(a.len() > 0 && b.len() > 0);
+        num_columns = std::cmp::max(num_columns, a.len() + b.len() - 1);
+        let a_max = (BigUint::from(1u64) << (LIMB_WIDTH * a.len())) - 1u64;
+        let b_max = (BigUint::from(1u64) << (LIMB_WIDTH * b.len())) - 1u64;
+        max_total += a_max * b_max;
+    }
+    for c in adds.iter() {
+        assert!(c.len() > 0);
+        num_columns = std::cmp::max(num_columns, c.len());
+        max_total += (BigUint::from(1u64) << (LIMB_WIDTH * c.len())) - 1u64;
+    }
+    assert!(num_columns > 0, "nothing to accumulate");
+
+    let num_limbs = std::cmp::max(num_columns, (max_total.bits() as usize + LIMB_WIDTH - 1) / LIMB_WIDTH);
+
+    let all_constant = muls.iter().all(|(a, b)| a.iter().chain(b.iter()).all(|x| x.is_constant())) &&
+        adds.iter().all(|c| c.iter().all(|x| x.is_constant()));
+
+    if all_constant {
+        let mut total = BigUint::from(0u64);
+        for (a, b) in muls.iter() {
+            total += get_limbs_value(a).unwrap() * get_limbs_value(b).unwrap();
+        }
+        for c in adds.iter() {
+            total += get_limbs_value(c).unwrap();
+        }
+
+        let limbs = split_into_fixed_number_of_limbs(total, LIMB_WIDTH, num_limbs);
+
+        return Ok(limbs.into_iter().map(|el| Num::Constant(biguint_to_fe(el))).collect());
+    }
+
+    let mut columns: Vec<LinearCombination<E>> = (0..num_limbs).map(|_| LinearCombination::zero()).collect();
+    let mut columns_max = vec![BigUint::from(0u64); num_limbs];
+    let mut columns_values = vec![Some(BigUint::from(0u64)); num_limbs];
+
+    let limb_max_squared = limb_max.clone() * &limb_max;
+
+    for (a, b) in muls.iter() {
+        for (i, a_limb) in a.iter().enumerate() {
+            for (j, b_limb) in b.iter().enumerate() {
+                // multiplication by a constant limb goes into the column as a coefficient
+                match (a_limb, b_limb) {
+                    (Num::Constant(c), x) | (x, Num::Constant(c)) => {
+                        columns[i + j].add_assign_number_with_coeff(x, *c);
+                        columns_max[i + j] += limb_max.clone() * fe_to_biguint(c);
+                    },
+                    _ => {
+                        let product = a_limb.mul(cs, b_limb)?;
+                        columns[i + j].add_assign_number_with_coeff(&product, E::Fr::one());
+                        columns_max[i + j] += &limb_max_squared;
+                    }
+                }
+                columns_values[i + j] = match (columns_values[i + j].take(), a_limb.get_value(), b_limb.get_value()) {
+                    (Some(acc), Some(x), Some(y)) => Some(acc + fe_to_biguint(&x) * fe_to_biguint(&y)),
+                    _ => None
+                };
+            }
+        }
+    }
+
+    for c in adds.iter() {
+        for (i, c_limb) in c.iter().enumerate() {
+            columns[i].add_assign_number_with_coeff(c_limb, E::Fr::one());
+            columns_max[i] += &limb_max;
+            columns_values[i] = match (columns_values[i].take(), c_limb.get_value()) {
+                (Some(acc), Some(v)) => Some(acc + fe_to_biguint(&v)),
+                _ => None
+            };
+        }
+    }
+
+    normalize_columns(cs, columns, columns_max, columns_values)
+}
+
+// takes wide columns (as linear combinations) with known maximum values and witnesses, and propagates carries
+// to get 64-bit range checked limbs; carry out of the last column is enforced to be zero
+pub(crate) fn normalize_columns<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    columns: Vec<LinearCombination<E>>,
+    columns_max: Vec<BigUint>,
+    columns_values: Vec<Option<BigUint>>
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    normalize_columns_impl(cs, columns, columns_max, columns_values, false)
+}
+
+// same as normalize_columns, but carry out of the last column is range checked and dropped,
+// so the result is the value of the columns mod 2^(64 * n)
+pub(crate) fn normalize_columns_wrapping<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    columns: Vec<LinearCombination<E>>,
+    columns_max: Vec<BigUint>,
+    columns_values: Vec<Option<BigUint>>
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    normalize_columns_impl(cs, columns, columns_max, columns_values, true)
+}
+
+fn normalize_columns_impl<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    columns: Vec<LinearCombination<E>>,
+    columns_max: Vec<BigUint>,
+    columns_values: Vec<Option<BigUint>>,
+    drop_final_carry: bool
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert_eq!(columns.len(), columns_max.len());
+    assert_eq!(columns.len(), columns_values.len());
+
+    let num_limbs = columns.len();
+    let limb_modulus = BigUint::from(1u64) << LIMB_WIDTH;
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+    let mut minus_shift = biguint_to_fe::<E::Fr>(limb_modulus.clone());
+    minus_shift.negate();
+
+    let mut result = Vec::with_capacity(num_limbs);
+
+    let mut carry: Option<Num<E>> = None;
+    let mut carry_max = BigUint::from(0u64);
+    let mut carry_value = Some(BigUint::from(0u64));
+
+    let it = columns.into_iter().zip(columns_max.into_iter()).zip(columns_values.into_iter());
+    for (idx, ((mut column, column_max), column_value)) in it.enumerate() {
+        let is_last = idx == num_limbs - 1;
+
+        if let Some(c) = carry.take() {
+            column.add_assign_number_with_coeff(&c, E::Fr::one());
+        }
+
+        let total_max = column_max + &carry_max;
+        assert!((total_max.bits() as usize) < E::Fr::CAPACITY as usize, "column overflows the field");
+        let total_value = match (column_value, carry_value.take()) {
+            (Some(v), Some(c)) => Some(v + c),
+            _ => None
+        };
+
+        let limb_value = total_value.as_ref().map(|v| v.clone() % &limb_modulus);
+        let limb = Num::alloc(cs, some_biguint_to_fe(&limb_value))?;
+        enforce_limb_width(cs, &limb, LIMB_WIDTH)?;
+        column.add_assign_number_with_coeff(&limb, minus_one);
+
+        if !is_last || drop_final_carry {
+            carry_max = total_max >> LIMB_WIDTH;
+            carry_value = total_value.map(|v| v >> LIMB_WIDTH);
+            if !carry_max.is_zero() {
+                let next_carry = Num::alloc(cs, some_biguint_to_fe(&carry_value))?;
+                enforce_limb_width(cs, &next_carry, carry_max.bits() as usize)?;
+                column.add_assign_number_with_coeff(&next_carry, minus_shift);
+                carry = Some(next_carry);
+            }
+        }
+
+        column.enforce_zero(cs)?;
+        result.push(limb);
+    }
+
+    Ok(result)
+}
+
+// computes a + b limb by limb: a_i + b_i + carry_{i-1} - 2^64 * carry_i = r_i, with every r_i
+// range checked to 64 bits and every carry being a boolean. Returns sum limbs and carries,
+// so the last carry is set iff a + b doesn't fit into n limbs, in which case sum is a + b - 2^(64 * n)
+pub fn add_with_carries<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<(Vec<Num<E>>, Vec<Boolean>), SynthesisError> {
+    let num_limbs = std::cmp::max(a.len(), b.len());
+    assert!(num_limbs > 0);
+
+    let zero = Num::Constant(E::Fr::zero());
+    let limb_modulus = BigUint::from(1u64) << LIMB_WIDTH;
+    let mut minus_shift = biguint_to_fe::<E::Fr>(limb_modulus.clone());
+    minus_shift.negate();
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut result = Vec::with_capacity(num_limbs);
+    let mut carries = Vec::with_capacity(num_limbs);
+    let mut carry = Boolean::constant(false);
+
+    for i in 0..num_limbs {
+        let a_limb = a.get(i).unwrap_or(&zero);
+        let b_limb = b.get(i).unwrap_or(&zero);
+
+        let witness = match (a_limb.get_value(), b_limb.get_value(), carry.get_value()) {
+            (Some(a), Some(b), Some(c)) => {
+                let sum = fe_to_biguint(&a) + fe_to_biguint(&b) + BigUint::from(c as u64);
+                if sum >= limb_modulus {
+                    Some((sum - &limb_modulus, true))
+                } else {
+                    Some((sum, false))
+                }
+            },
+            _ => None
+        };
+
+        let r = Num::alloc(cs, some_biguint_to_fe(&witness.as_ref().map(|(r, _)| r.clone())))?;
+        enforce_limb_width(cs, &r, LIMB_WIDTH)?;
+        let new_carry = Boolean::alloc(cs, witness.map(|(_, c)| c))?;
+
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(a_limb, E::Fr::one());
+        lc.add_assign_number_with_coeff(b_limb, E::Fr::one());
+        lc.add_assign_boolean_with_coeff(&carry, E::Fr::one());
+        lc.add_assign_boolean_with_coeff(&new_carry, minus_shift);
+        lc.add_assign_number_with_coeff(&r, minus_one);
+        lc.enforce_zero(cs)?;
+
+        result.push(r);
+        carries.push(new_carry);
+        carry = new_carry;
+    }
+
+    Ok((result, carries))
+}
+
+// computes a - b limb by limb: a_i - b_i - borrow_{i-1} + 2^64 * borrow_i = r_i, with every r_i
+// range checked to 64 bits and every borrow being a boolean. Returns difference limbs and borrows,
+// so the last borrow is set iff a < b, in which case difference is a - b + 2^(64 * n)
+pub fn sub_with_borrows<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<(Vec<Num<E>>, Vec<Boolean>), SynthesisError> {
+    let num_limbs = std::cmp::max(a.len(), b.len());
+    assert!(num_limbs > 0);
+
+    let zero = Num::Constant(E::Fr::zero());
+    let limb_modulus = BigUint::from(1u64) << LIMB_WIDTH;
+    let shift = biguint_to_fe::<E::Fr>(limb_modulus.clone());
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut result = Vec::with_capacity(num_limbs);
+    let mut borrows = Vec::with_capacity(num_limbs);
+    let mut borrow = Boolean::constant(false);
+
+    for i in 0..num_limbs {
+        let a_limb = a.get(i).unwrap_or(&zero);
+        let b_limb = b.get(i).unwrap_or(&zero);
+
+        let witness = match (a_limb.get_value(), b_limb.get_value(), borrow.get_value()) {
+            (Some(a), Some(b), Some(bw)) => {
+                let a = fe_to_biguint(&a);
+                let subtrahend = fe_to_biguint(&b) + BigUint::from(bw as u64);
+                if a >= subtrahend {
+                    Some((a - subtrahend, false))
+                } else {
+                    Some((a + &limb_modulus - subtrahend, true))
+                }
+            },
+            _ => None
+        };
+
+        let r = Num::alloc(cs, some_biguint_to_fe(&witness.as_ref().map(|(r, _)| r.clone())))?;
+        enforce_limb_width(cs, &r, LIMB_WIDTH)?;
+        let new_borrow = Boolean::alloc(cs, witness.map(|(_, bw)| bw))?;
+
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(a_limb, E::Fr::one());
+        lc.add_assign_number_with_coeff(b_limb, minus_one);
+        lc.add_assign_boolean_with_coeff(&borrow, minus_one);
+        lc.add_assign_boolean_with_coeff(&new_borrow, shift);
+        lc.add_assign_number_with_coeff(&r, minus_one);
+        lc.enforce_zero(cs)?;
+
+        result.push(r);
+        borrows.push(new_borrow);
+        borrow = new_borrow;
+    }
+
+    Ok((result, borrows))
+}
+
+// enforces that limbs above those needed to hold a `value_bits` wide value are zero,
+// so the same value can not be represented with spurious high limbs
+pub fn enforce_minimal_limbs<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    value_bits: usize
+) -> Result<(), SynthesisError> {
+    let num_limbs = (value_bits + LIMB_WIDTH - 1) / LIMB_WIDTH;
+    for limb in a.iter().skip(num_limbs) {
+        limb.assert_is_zero(cs)?;
+    }
+
+    Ok(())
+}
+
+// splits a 64-bit limb into (low, high) parts with low being `low_width` bits wide,
+// both parts are range checked
+pub(crate) fn split_limb<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    limb: &Num<E>,
+    low_width: usize
+) -> Result<(Num<E>, Num<E>), SynthesisError> {
+    assert!(low_width <= LIMB_WIDTH);
+
+    if let Num::Constant(c) = limb {
+        let value = fe_to_biguint(c);
+        let low = value.clone() % (BigUint::from(1u64) << low_width);
+        let high = value >> low_width;
+
+        return Ok((Num::Constant(biguint_to_fe(low)), Num::Constant(biguint_to_fe(high))));
+    }
+
+    let value = limb.get_value().map(|el| fe_to_biguint(&el));
+    let low_value = value.as_ref().map(|v| v.clone() % (BigUint::from(1u64) << low_width));
+    let high_value = value.map(|v| v >> low_width);
+
+    let low = Num::alloc(cs, some_biguint_to_fe(&low_value))?;
+    let high = Num::alloc(cs, some_biguint_to_fe(&high_value))?;
+    enforce_limb_width(cs, &low, low_width)?;
+    enforce_limb_width(cs, &high, LIMB_WIDTH - low_width)?;
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut lc = LinearCombination::zero();
+    lc.add_assign_number_with_coeff(&low, E::Fr::one());
+    lc.add_assign_number_with_coeff(&high, biguint_to_fe(BigUint::from(1u64) << low_width));
+    lc.add_assign_number_with_coeff(limb, minus_one);
+    lc.enforce_zero(cs)?;
+
+    Ok((low, high))
+}
+
+// returns limbs of a mod 2^k, the result always has ceil(k / 64) limbs
+pub fn mod_pow2<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    k: usize
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let num_limbs = (k + LIMB_WIDTH - 1) / LIMB_WIDTH;
+    let zero = Num::Constant(E::Fr::zero());
+
+    let mut result = Vec::with_capacity(num_limbs);
+    for i in 0..num_limbs {
+        let limb = a.get(i).unwrap_or(&zero);
+        let width = std::cmp::min(LIMB_WIDTH, k - i * LIMB_WIDTH);
+        if width == LIMB_WIDTH {
+            result.push(limb.clone());
+        } else {
+            let (low, _) = split_limb(cs, limb, width)?;
+            result.push(low);
+        }
+    }
+
+    Ok(result)
+}
+
+// returns true iff a = b mod 2^k
+pub fn equals_mod_pow2<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>],
+    k: usize
+) -> Result<Boolean, SynthesisError> {
+    let a_low = mod_pow2(cs, a, k)?;
+    let b_low = mod_pow2(cs, b, k)?;
+
+    let mut result = Boolean::constant(true);
+    for (a, b) in a_low.iter().zip(b_low.iter()) {
+        let limbs_are_equal = Num::equals(cs, a, b)?;
+        result = Boolean::and(cs, &result, &limbs_are_equal)?;
+    }
+
+    Ok(result)
+}
+
+// enforces balance_after = balance_before - amount together with balance_before >= amount,
+// the latter is the same as the final borrow of the subtraction being zero
+pub fn enforce_debit<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    balance_before: &[Num<E>],
+    balance_after: &[Num<E>],
+    amount: &[Num<E>]
+) -> Result<(), SynthesisError> {
+    let (difference, borrows) = sub_with_borrows(cs, balance_before, amount)?;
+    Boolean::enforce_equal(cs, borrows.last().unwrap(), &Boolean::constant(false))?;
+
+    let zero = Num::Constant(E::Fr::zero());
+    let num_limbs = std::cmp::max(difference.len(), balance_after.len());
+    for i in 0..num_limbs {
+        let expected = difference.get(i).unwrap_or(&zero);
+        let actual = balance_after.get(i).unwrap_or(&zero);
+        expected.enforce_equal(cs, actual)?;
+    }
+
+    Ok(())
+}
+
+// returns -a mod 2^(64 * n) if flag is set and a otherwise
+pub fn conditionally_negate<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    flag: &Boolean
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let (negated, _) = sub_with_borrows(cs, &[], a)?;
+
+    let mut result = Vec::with_capacity(a.len());
+    for (negated_limb, limb) in negated.iter().zip(a.iter()) {
+        result.push(Num::conditionally_select(cs, flag, negated_limb, limb)?);
+    }
+
+    Ok(result)
+}
+
+// returns |a - b| and a sign which is set iff a < b, so a == b gives zero magnitude and unset sign
+pub fn sub_signed<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<(Vec<Num<E>>, Boolean), SynthesisError> {
+    let (difference, borrows) = sub_with_borrows(cs, a, b)?;
+    let sign = *borrows.last().unwrap();
+    let magnitude = conditionally_negate(cs, &difference, &sign)?;
+
+    Ok((magnitude, sign))
+}
+
+// enforces value = sum_i digit_i * base^i where every digit is in [-h, base - 1 - h] for h = floor(base/2),
+// that is [-(base/2), base/2) for even bases and [-(base-1)/2, (base-1)/2] for odd ones.
+// Every digit is shifted by h into [0, base) and range checked, so the relation becomes
+// value + h * sum_i base^i = sum_i (digit_i + h) * base^i over nonnegative integers,
+// both sides of which are normalized into 64-bit limbs and compared limb by limb
+pub fn enforce_signed_digit_decomposition<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    value: &[Num<E>],
+    digits: &[Num<E>],
+    base: u64
+) -> Result<(), SynthesisError> {
+    assert!(base >= 2);
+    assert!(digits.len() > 0);
+
+    let half = base / 2;
+    let digit_max = BigUint::from(base - 1);
+    let half_fe = u64_to_fe::<E::Fr>(half);
+
+    let mut offset = BigUint::from(0u64);
+    let mut weight = BigUint::from(1u64);
+    let mut weights = Vec::with_capacity(digits.len());
+    for _ in 0..digits.len() {
+        offset += BigUint::from(half) * &weight;
+        weights.push(weight.clone());
+        weight *= base;
+    }
+
+    // weight is base^n now, so the shifted digits sum up to at most base^n - 1
+    let num_limbs = ((weight - 1u64).bits() as usize + LIMB_WIDTH - 1) / LIMB_WIDTH;
+    let num_limbs = std::cmp::max(num_limbs, 1);
+
+    let mut columns: Vec<LinearCombination<E>> = (0..num_limbs).map(|_| LinearCombination::zero()).collect();
+    let mut columns_max = vec![BigUint::from(0u64); num_limbs];
+    let mut columns_values = vec![Some(BigUint::from(0u64)); num_limbs];
+
+    for (digit, weight) in digits.iter().zip(weights.into_iter()) {
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(digit, E::Fr::one());
+        lc.add_assign_constant(half_fe);
+        let shifted = lc.into_num(cs)?;
+        enforce_at_most_constant(cs, &shifted, &digit_max)?;
+
+        let shifted_value = shifted.get_value().map(|el| fe_to_biguint(&el));
+        let weight_limbs = split_into_fixed_number_of_limbs(weight, LIMB_WIDTH, num_limbs);
+        for (j, weight_limb) in weight_limbs.into_iter().enumerate() {
+            if weight_limb.is_zero() {
+                continue;
+            }
+            columns[j].add_assign_number_with_coeff(&shifted, biguint_to_fe(weight_limb.clone()));
+            columns_max[j] += digit_max.clone() * &weight_limb;
+            columns_values[j] = match (columns_values[j].take(), shifted_value.as_ref()) {
+                (Some(acc), Some(v)) => Some(acc + v * &weight_limb),
+                _ => None
+            };
+        }
+    }
+
+    let digits_sum = normalize_columns(cs, columns, columns_max, columns_values)?;
+
+    let offset_limbs: Vec<Num<E>> = split_into_fixed_number_of_limbs(offset, LIMB_WIDTH, num_limbs).into_iter()
+        .map(|el| Num::Constant(biguint_to_fe(el))).collect();
+    let shifted_value = mul_accumulate(cs, &[], &[value.to_vec(), offset_limbs])?;
+
+    let zero = Num::Constant(E::Fr::zero());
+    for i in 0..std::cmp::max(digits_sum.len(), shifted_value.len()) {
+        let lhs = shifted_value.get(i).unwrap_or(&zero);
+        let rhs = digits_sum.get(i).unwrap_or(&zero);
+        lhs.enforce_equal(cs, rhs)?;
+    }
+
+    Ok(())
+}
+
+// divides a limbed number by a constant: witnesses q and r and enforces a = q * d + r with 0 <= r < d.
+// Since d is a constant, q_i * d goes directly into linear combinations, so no multiplication gates are used
+pub fn div_by_constant<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    d: u64
+) -> Result<(Vec<Num<E>>, Num<E>), SynthesisError> {
+    assert!(d > 0, "division by zero");
+    assert!(a.len() > 0);
+
+    let divisor = BigUint::from(d);
+    let limb_max = (BigUint::from(1u64) << LIMB_WIDTH) - 1u64;
+    let a_value = get_limbs_value(a);
+    let q_value = a_value.as_ref().map(|v| v.clone() / &divisor);
+    let r_value = a_value.map(|v| v % &divisor);
+
+    let q_limbs_values = split_some_into_fixed_number_of_limbs(q_value.clone(), LIMB_WIDTH, a.len());
+    let mut quotient = Vec::with_capacity(a.len());
+    for limb_value in q_limbs_values.iter() {
+        let limb = Num::alloc(cs, some_biguint_to_fe(limb_value))?;
+        enforce_limb_width(cs, &limb, LIMB_WIDTH)?;
+        quotient.push(limb);
+    }
+    let remainder = Num::alloc(cs, some_biguint_to_fe(&r_value))?;
+    enforce_at_most_constant(cs, &remainder, &(divisor.clone() - 1u64))?;
+
+    // one more column to hold the carry, q * d + r is less than 2^(64 * (n + 1)) for any q and r
+    let num_limbs = a.len() + 1;
+    let mut columns: Vec<LinearCombination<E>> = (0..num_limbs).map(|_| LinearCombination::zero()).collect();
+    let mut columns_max = vec![BigUint::from(0u64); num_limbs];
+    let mut columns_values = vec![Some(BigUint::from(0u64)); num_limbs];
+
+    let d_fe = u64_to_fe::<E::Fr>(d);
+    for (i, (limb, limb_value)) in quotient.iter().zip(q_limbs_values.into_iter()).enumerate() {
+        columns[i].add_assign_number_with_coeff(limb, d_fe);
+        columns_max[i] = limb_max.clone() * &divisor;
+        columns_values[i] = limb_value.map(|v| v * &divisor);
+    }
+    columns[0].add_assign_number_with_coeff(&remainder, E::Fr::one());
+    columns_max[0] += divisor.clone() - 1u64;
+    columns_values[0] = match (columns_values[0].take(), r_value) {
+        (Some(acc), Some(r)) => Some(acc + r),
+        _ => None
+    };
+
+    let recomputed = normalize_columns(cs, columns, columns_max, columns_values)?;
+    let zero = Num::Constant(E::Fr::zero());
+    for (i, limb) in recomputed.iter().enumerate() {
+        limb.enforce_equal(cs, a.get(i).unwrap_or(&zero))?;
+    }
+
+    Ok((quotient, remainder))
+}
+
+// enforces new > old, which is the same as old - new having a final borrow. Both numbers have
+// a fixed number of range checked limbs, so new can not wrap around past 2^(64 * n) - 1
+pub fn enforce_nonce_advance<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    new: &[Num<E>],
+    old: &[Num<E>]
+) -> Result<(), SynthesisError> {
+    let (_, borrows) = sub_with_borrows(cs, old, new)?;
+    Boolean::enforce_equal(cs, borrows.last().unwrap(), &Boolean::constant(true))?;
+
+    Ok(())
+}
+
+// enforces that a equals base in all bits outside of the [mask_low_bit, mask_high_bit) window,
+// bits inside the window are free. Every limb that intersects the window is enforced to be
+// (base limb with window bits cleared) + m * 2^l, where m is range checked to the window width in this limb
+pub fn enforce_masked_equal<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    base: &BigUint,
+    mask_low_bit: usize,
+    mask_high_bit: usize
+) -> Result<(), SynthesisError> {
+    assert!(mask_low_bit <= mask_high_bit);
+    assert!(base.bits() as usize <= a.len() * LIMB_WIDTH);
+
+    let base_limbs = split_into_fixed_number_of_limbs(base.clone(), LIMB_WIDTH, a.len());
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    for (i, (limb, base_limb)) in a.iter().zip(base_limbs.into_iter()).enumerate() {
+        let limb_start = i * LIMB_WIDTH;
+        let low = std::cmp::max(mask_low_bit, limb_start) - limb_start;
+        let high = std::cmp::min(mask_high_bit, limb_start + LIMB_WIDTH).saturating_sub(limb_start);
+
+        if high <= low {
+            limb.enforce_equal(cs, &Num::Constant(biguint_to_fe(base_limb)))?;
+            continue;
+        }
+
+        let window_width = high - low;
+        let window_mask = ((BigUint::from(1u64) << window_width) - 1u64) << low;
+        let outside = base_limb.clone() ^ (base_limb & &window_mask);
+
+        let inside_value = limb.get_value().map(|el| (fe_to_biguint(&el) & &window_mask) >> low);
+        let inside = Num::alloc(cs, some_biguint_to_fe(&inside_value))?;
+        enforce_limb_width(cs, &inside, window_width)?;
+
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_constant(biguint_to_fe(outside));
+        lc.add_assign_number_with_coeff(&inside, biguint_to_fe(BigUint::from(1u64) << low));
+        lc.add_assign_number_with_coeff(limb, minus_one);
+        lc.enforce_zero(cs)?;
+    }
+
+    Ok(())
+}
+
+// enforces b_i = a_rev(i) where rev reverses log2(n) bits of the index, n must be a power of two
+pub fn enforce_bit_reversed_limbs<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<(), SynthesisError> {
+    assert_eq!(a.len(), b.len());
+    assert!(a.len().is_power_of_two(), "number of limbs must be a power of two");
+
+    let log_n = a.len().trailing_zeros();
+    for (i, b_limb) in b.iter().enumerate() {
+        let j = if log_n == 0 { 0 } else { i.reverse_bits() >> (std::mem::size_of::<usize>() as u32 * 8 - log_n) };
+        b_limb.enforce_equal(cs, &a[j])?;
+    }
+
+    Ok(())
+}
+
+// returns true iff all limbs are zero, constant limbs don't cost anything
+pub fn is_zero<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>]
+) -> Result<Boolean, SynthesisError> {
+    let mut result = Boolean::constant(true);
+    for limb in a.iter() {
+        let limb_is_zero = limb.is_zero(cs)?;
+        result = Boolean::and(cs, &result, &limb_is_zero)?;
+    }
+
+    Ok(result)
+}
+
+// returns true iff a = b, limbs are compared pairwise, so constant pairs cost nothing
+pub fn equals<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Boolean, SynthesisError> {
+    let zero = Num::Constant(E::Fr::zero());
+
+    let mut result = Boolean::constant(true);
+    for i in 0..std::cmp::max(a.len(), b.len()) {
+        let limbs_are_equal = Num::equals(cs, a.get(i).unwrap_or(&zero), b.get(i).unwrap_or(&zero))?;
+        result = Boolean::and(cs, &result, &limbs_are_equal)?;
+    }
+
+    Ok(result)
+}
+
+// reduces a limbed number modulo the scalar field characteristic and returns the result
+// as a native field element. Reduction is the same as for any other constant modulus, and then
+// r is packed into a single variable, which is exact since r < p
+pub fn reduce_to_field<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>]
+) -> Result<AllocatedNum<E>, SynthesisError> {
+    let modulus = repr_to_biguint::<E::Fr>(&E::Fr::char());
+    let (_, remainder) = reduce_by_constant_modulus(cs, a, &modulus, false)?;
+
+    pack_limbs(&remainder).into_allocated_num(cs)
+}
+
+// returns true iff a < b, which is the final borrow of a - b
+pub fn less_than<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Boolean, SynthesisError> {
+    let (_, borrows) = sub_with_borrows(cs, a, b)?;
+
+    Ok(*borrows.last().unwrap())
+}
+
+// returns a if flag is set and b otherwise, a constant flag or a pair of equal constant limbs
+// don't cost anything, otherwise every limb takes a single gate
+pub fn conditionally_select<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    flag: &Boolean,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert_eq!(a.len(), b.len());
+
+    if let Boolean::Constant(flag) = flag {
+        return Ok(if *flag { a.to_vec() } else { b.to_vec() });
+    }
+
+    let mut result = Vec::with_capacity(a.len());
+    for (a_limb, b_limb) in a.iter().zip(b.iter()) {
+        let selected = match (a_limb, b_limb) {
+            (Num::Constant(x), Num::Constant(y)) if x == y => Num::Constant(*x),
+            _ => Num::conditionally_select(cs, flag, a_limb, b_limb)?
+        };
+        result.push(selected);
+    }
+
+    Ok(result)
+}
+
+// returns a << bits mod 2^(64 * n), every limb that ends up in the result is split at 64 - s bits
+// where s = bits mod 64, and the parts are recombined into new limbs
+pub fn shl<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    bits: usize
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let num_limbs = a.len();
+    let zero = Num::Constant(E::Fr::zero());
+    if bits >= num_limbs * LIMB_WIDTH {
+        return Ok(vec![zero; num_limbs]);
+    }
+
+    let limb_shift = bits / LIMB_WIDTH;
+    let bit_shift = bits % LIMB_WIDTH;
+    if bit_shift == 0 {
+        let mut result = vec![zero; limb_shift];
+        result.extend_from_slice(&a[..(num_limbs - limb_shift)]);
+
+        return Ok(result);
+    }
+
+    let shift = biguint_to_fe::<E::Fr>(BigUint::from(1u64) << bit_shift);
+    let mut result = vec![zero; limb_shift];
+    let mut carried_high = Num::Constant(E::Fr::zero());
+    for limb in a[..(num_limbs - limb_shift)].iter() {
+        let (low, high) = split_limb(cs, limb, LIMB_WIDTH - bit_shift)?;
+
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(&low, shift);
+        lc.add_assign_number_with_coeff(&carried_high, E::Fr::one());
+        result.push(lc.into_num(cs)?);
+
+        carried_high = high;
+    }
+
+    Ok(result)
+}
+
+// returns a >> bits, limbs that are shifted out completely are not touched at all
+pub fn shr<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    bits: usize
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let num_limbs = a.len();
+    let zero = Num::Constant(E::Fr::zero());
+    if bits >= num_limbs * LIMB_WIDTH {
+        return Ok(vec![zero; num_limbs]);
+    }
+
+    let limb_shift = bits / LIMB_WIDTH;
+    let bit_shift = bits % LIMB_WIDTH;
+    if bit_shift == 0 {
+        let mut result = a[limb_shift..].to_vec();
+        result.resize(num_limbs, zero);
+
+        return Ok(result);
+    }
+
+    let mut parts = Vec::with_capacity(num_limbs - limb_shift);
+    for limb in a[limb_shift..].iter() {
+        parts.push(split_limb(cs, limb, bit_shift)?);
+    }
+
+    let shift = biguint_to_fe::<E::Fr>(BigUint::from(1u64) << (LIMB_WIDTH - bit_shift));
+    let mut result = Vec::with_capacity(num_limbs);
+    for (i, (_, high)) in parts.iter().enumerate() {
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(high, E::Fr::one());
+        if let Some((next_low, _)) = parts.get(i + 1) {
+            lc.add_assign_number_with_coeff(next_low, shift);
+        }
+        result.push(lc.into_num(cs)?);
+    }
+    result.resize(num_limbs, zero);
+
+    Ok(result)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitwiseOp {
+    And,
+    Or,
+    Xor,
+}
+
+// chunks are as wide as the range check table used across this module. The combined bitwise table has
+// 2^(2w) rows for w-bit chunks, so 16-bit chunks are unpacked into bits and combined by the boolean gadgets
+// instead, chunks of at most 8 bits go through the combined table and are cheaper in gates
+pub const DEFAULT_BITWISE_CHUNK_WIDTH: usize = 16;
+
+// returns the combined bitwise table for chunks of the given width, chunk width must divide 64
+// and be less than 16 for the or = and + xor check below to be unambiguous
+fn inscribe_bitwise_table<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    chunk_width: usize
+) -> Result<Arc<LookupTableApplication<E>>, SynthesisError> {
+    use crate::plonk::circuit::bigint_new::{inscribe_default_bitop_range_table, CombinedBitwiseLogicRangeTable};
+
+    let name: &'static str = match chunk_width {
+        1 => "Table for bitwise logical ops over 1-bit chunks",
+        2 => "Table for bitwise logical ops over 2-bit chunks",
+        4 => "Table for bitwise logical ops over 4-bit chunks",
+        8 => return inscribe_default_bitop_range_table(cs),
+        _ => panic!("unsupported chunk width {} for bitwise ops", chunk_width)
+    };
+
+    get_or_create_table(
+        cs, name, || {
+            let columns3 = vec![
+                PolyIdentifier::VariablesPolynomial(0),
+                PolyIdentifier::VariablesPolynomial(1),
+                PolyIdentifier::VariablesPolynomial(2)
+            ];
+
+            LookupTableApplication::new(
+                name, CombinedBitwiseLogicRangeTable::new(name, chunk_width), columns3, None, true
+            )
+        }
+    )
+}
+
+// looks up (x, y) in the combined bitwise table, which also enforces both keys to be chunk-wide
+fn apply_bitwise_table_lookup<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    table: &Arc<LookupTableApplication<E>>,
+    x: &AllocatedNum<E>,
+    y: &AllocatedNum<E>
+) -> Result<AllocatedNum<E>, SynthesisError> {
+    let z = match (x.get_value(), y.get_value()) {
+        (Some(x), Some(y)) => {
+            let res = table.query(&[x, y])?;
+            AllocatedNum::alloc(cs, || Ok(res[0]))?
+        },
+        _ => AllocatedNum::alloc(cs, || Err(SynthesisError::AssignmentMissing))?
+    };
+
+    let dummy = CS::get_dummy_variable();
+    let vars = [x.get_variable(), y.get_variable(), z.get_variable(), dummy];
+    let (_, coeffs) = CS::MainGate::format_term(MainGateTerm::new(), dummy)?;
+
+    cs.begin_gates_batch_for_step()?;
+    cs.apply_single_lookup_gate(&vars[..table.width()], table.clone())?;
+    cs.new_gate_in_batch(&CS::MainGate::default(), &coeffs, &vars, &[])?;
+    cs.end_gates_batch_for_step()?;
+
+    Ok(z)
+}
+
+// chunks of a single limb pair, for every chunk pair we keep witnessed `and` and `xor` chunks,
+// or bit pairs when chunks are too wide for the combined table
+#[derive(Clone, Debug)]
+enum BitwiseLimbChunks<E: Engine> {
+    Constant(u64, u64),
+    Chunks(Vec<(AllocatedNum<E>, AllocatedNum<E>)>),
+    Bits(Vec<(Boolean, Boolean)>),
+}
+
+// chunk decomposition of a pair of operands, once it's done any bitwise op over the same operands
+// is only a linear combination of the cached chunks
+#[derive(Clone, Debug)]
+pub struct BitwiseOperands<E: Engine> {
+    limbs: Vec<BitwiseLimbChunks<E>>,
+}
+
+// keeps the combined bitwise table and the chunk width, so that many operand pairs can be decomposed
+// without looking the table up every time. There is no table for 16-bit chunks
+#[derive(Clone)]
+pub struct BitwiseCtx<E: Engine> {
+    table: Option<Arc<LookupTableApplication<E>>>,
+    chunk_width: usize,
+}
+
+impl<E: Engine> BitwiseCtx<E> {
+    pub fn new<CS: ConstraintSystem<E>>(cs: &mut CS, chunk_width: usize) -> Result<Self, SynthesisError> {
+        let table = if chunk_width == 16 {
+            None
+        } else {
+            Some(inscribe_bitwise_table(cs, chunk_width)?)
+        };
+        assert!(chunk_width <= 16 && LIMB_WIDTH % chunk_width == 0);
+
+        Ok(Self { table, chunk_width })
+    }
+
+    pub fn chunk_width(&self) -> usize {
+        self.chunk_width
+    }
+
+    // every limb pair is split into chunks of `chunk_width` bits, and for every chunk pair the table gives
+    // z = and + 2^16 * or + 2^32 * xor. We witness `and` and `xor` chunks, check them to be chunk wide by
+    // one more lookup, and enforce z = (1 + 2^16) * and + (2^16 + 2^32) * xor, which is unambiguous
+    // for w < 16 as or = and + xor. Every chunk takes 2 lookups, so wider chunks are cheaper in gates,
+    // but the table grows as 2^(2w)
+    pub fn decompose<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        a: &[Num<E>],
+        b: &[Num<E>]
+    ) -> Result<BitwiseOperands<E>, SynthesisError> {
+        assert_eq!(a.len(), b.len());
+
+        let table = match self.table {
+            Some(ref table) => table,
+            None => return self.decompose_into_bits(cs, a, b)
+        };
+
+        let num_chunks = LIMB_WIDTH / self.chunk_width;
+        let chunk_mask = (1u64 << self.chunk_width) - 1;
+        let chunk_shift = u64_to_fe::<E::Fr>(1u64 << self.chunk_width);
+
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+        let and_coeff = u64_to_fe::<E::Fr>(1 + (1u64 << 16));
+        let xor_coeff = u64_to_fe::<E::Fr>((1u64 << 16) + (1u64 << 32));
+
+        let mut limbs = Vec::with_capacity(a.len());
+        for (a_limb, b_limb) in a.iter().zip(b.iter()) {
+            if let (Num::Constant(x), Num::Constant(y)) = (a_limb, b_limb) {
+                let x = x.into_repr().as_ref()[0];
+                let y = y.into_repr().as_ref()[0];
+                limbs.push(BitwiseLimbChunks::Constant(x, y));
+                continue;
+            }
+
+            let a_value = a_limb.get_value().map(|el| el.into_repr().as_ref()[0]);
+            let b_value = b_limb.get_value().map(|el| el.into_repr().as_ref()[0]);
+
+            let mut a_lc = LinearCombination::zero();
+            a_lc.add_assign_number_with_coeff(a_limb, minus_one);
+            let mut b_lc = LinearCombination::zero();
+            b_lc.add_assign_number_with_coeff(b_limb, minus_one);
+            let mut coeff = E::Fr::one();
+
+            let mut chunks = Vec::with_capacity(num_chunks);
+            for k in 0..num_chunks {
+                let shift = k * self.chunk_width;
+                let x_value = a_value.map(|v| (v >> shift) & chunk_mask);
+                let y_value = b_value.map(|v| (v >> shift) & chunk_mask);
+
+                let x = AllocatedNum::alloc(cs, || Ok(u64_to_fe(*x_value.get()?)))?;
+                let y = AllocatedNum::alloc(cs, || Ok(u64_to_fe(*y_value.get()?)))?;
+                let z = apply_bitwise_table_lookup(cs, table, &x, &y)?;
+
+                let and = AllocatedNum::alloc(cs, || Ok(u64_to_fe(*x_value.get()? & *y_value.get()?)))?;
+                let xor = AllocatedNum::alloc(cs, || Ok(u64_to_fe(*x_value.get()? ^ *y_value.get()?)))?;
+                // we are only interested in the keys being range checked here
+                apply_bitwise_table_lookup(cs, table, &and, &xor)?;
+
+                let mut lc = LinearCombination::zero();
+                lc.add_assign_variable_with_coeff(&z, minus_one);
+                lc.add_assign_variable_with_coeff(&and, and_coeff);
+                lc.add_assign_variable_with_coeff(&xor, xor_coeff);
+                lc.enforce_zero(cs)?;
+
+                a_lc.add_assign_variable_with_coeff(&x, coeff);
+                b_lc.add_assign_variable_with_coeff(&y, coeff);
+                coeff.mul_assign(&chunk_shift);
+
+                chunks.push((and, xor));
+            }
+
+            a_lc.enforce_zero(cs)?;
+            b_lc.enforce_zero(cs)?;
+            limbs.push(BitwiseLimbChunks::Chunks(chunks));
+        }
+
+        Ok(BitwiseOperands { limbs })
+    }
+
+    // every chunk is unpacked into bits, which also range checks it, constant limbs give constant bits
+    fn decompose_into_bits<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        a: &[Num<E>],
+        b: &[Num<E>]
+    ) -> Result<BitwiseOperands<E>, SynthesisError> {
+        let mut limbs = Vec::with_capacity(a.len());
+        for (a_limb, b_limb) in a.iter().zip(b.iter()) {
+            if let (Num::Constant(x), Num::Constant(y)) = (a_limb, b_limb) {
+                let x = x.into_repr().as_ref()[0];
+                let y = y.into_repr().as_ref()[0];
+                limbs.push(BitwiseLimbChunks::Constant(x, y));
+                continue;
+            }
+
+            let a_bits = self.unpack_limb_by_chunks(cs, a_limb)?;
+            let b_bits = self.unpack_limb_by_chunks(cs, b_limb)?;
+            limbs.push(BitwiseLimbChunks::Bits(a_bits.into_iter().zip(b_bits.into_iter()).collect()));
+        }
+
+        Ok(BitwiseOperands { limbs })
+    }
+
+    fn unpack_limb_by_chunks<CS: ConstraintSystem<E>>(&self, cs: &mut CS, limb: &Num<E>) -> Result<Vec<Boolean>, SynthesisError> {
+        if limb.is_constant() {
+            return unpack_flags(cs, limb, LIMB_WIDTH);
+        }
+
+        let limb_value = limb.get_value().map(|el| el.into_repr().as_ref()[0]);
+        let chunk_mask = (1u64 << self.chunk_width) - 1;
+        let chunk_shift = u64_to_fe::<E::Fr>(1u64 << self.chunk_width);
+
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(limb, minus_one);
+        let mut coeff = E::Fr::one();
+
+        let mut bits = Vec::with_capacity(LIMB_WIDTH);
+        for k in 0..(LIMB_WIDTH / self.chunk_width) {
+            let chunk_value = limb_value.map(|v| (v >> (k * self.chunk_width)) & chunk_mask);
+            let chunk = Num::alloc(cs, chunk_value.map(u64_to_fe))?;
+            bits.extend(unpack_flags(cs, &chunk, self.chunk_width)?);
+
+            lc.add_assign_number_with_coeff(&chunk, coeff);
+            coeff.mul_assign(&chunk_shift);
+        }
+        lc.enforce_zero(cs)?;
+
+        Ok(bits)
+    }
+
+    // reassembles the result of the operation from the cached chunks, costs a single linear combination per limb
+    pub fn apply<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        operands: &BitwiseOperands<E>,
+        op: BitwiseOp
+    ) -> Result<Vec<Num<E>>, SynthesisError> {
+        let chunk_shift = u64_to_fe::<E::Fr>(1u64 << self.chunk_width);
+
+        let mut result = Vec::with_capacity(operands.limbs.len());
+        for limb in operands.limbs.iter() {
+            let chunks = match limb {
+                BitwiseLimbChunks::Constant(x, y) => {
+                    let value = match op {
+                        BitwiseOp::And => x & y,
+                        BitwiseOp::Or => x | y,
+                        BitwiseOp::Xor => x ^ y,
+                    };
+                    result.push(Num::Constant(u64_to_fe(value)));
+                    continue;
+                },
+                BitwiseLimbChunks::Chunks(chunks) => chunks,
+                BitwiseLimbChunks::Bits(bits) => {
+                    let mut result_lc = LinearCombination::zero();
+                    let mut coeff = E::Fr::one();
+                    for (x, y) in bits.iter() {
+                        let bit = match op {
+                            BitwiseOp::And => Boolean::and(cs, x, y)?,
+                            BitwiseOp::Or => Boolean::or(cs, x, y)?,
+                            BitwiseOp::Xor => Boolean::xor(cs, x, y)?,
+                        };
+                        result_lc.add_assign_boolean_with_coeff(&bit, coeff);
+                        coeff.double();
+                    }
+                    result.push(result_lc.into_num(cs)?);
+                    continue;
+                }
+            };
+
+            let mut result_lc = LinearCombination::zero();
+            let mut coeff = E::Fr::one();
+            for (and, xor) in chunks.iter() {
+                match op {
+                    BitwiseOp::And => result_lc.add_assign_variable_with_coeff(and, coeff),
+                    BitwiseOp::Xor => result_lc.add_assign_variable_with_coeff(xor, coeff),
+                    BitwiseOp::Or => {
+                        result_lc.add_assign_variable_with_coeff(and, coeff);
+                        result_lc.add_assign_variable_with_coeff(xor, coeff);
+                    }
+                }
+                coeff.mul_assign(&chunk_shift);
+            }
+            result.push(result_lc.into_num(cs)?);
+        }
+
+        Ok(result)
+    }
+
+    pub fn bitand<CS: ConstraintSystem<E>>(&self, cs: &mut CS, operands: &BitwiseOperands<E>) -> Result<Vec<Num<E>>, SynthesisError> {
+        self.apply(cs, operands, BitwiseOp::And)
+    }
+
+    pub fn bitor<CS: ConstraintSystem<E>>(&self, cs: &mut CS, operands: &BitwiseOperands<E>) -> Result<Vec<Num<E>>, SynthesisError> {
+        self.apply(cs, operands, BitwiseOp::Or)
+    }
+
+    pub fn bitxor<CS: ConstraintSystem<E>>(&self, cs: &mut CS, operands: &BitwiseOperands<E>) -> Result<Vec<Num<E>>, SynthesisError> {
+        self.apply(cs, operands, BitwiseOp::Xor)
+    }
+}
+
+// applies a single bitwise operation limb by limb, use `BitwiseCtx` directly to apply several
+// operations to the same operands over one decomposition
+pub fn apply_bitwise_op<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>],
+    op: BitwiseOp,
+    chunk_width: usize
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let ctx = BitwiseCtx::new(cs, chunk_width)?;
+    let operands = ctx.decompose(cs, a, b)?;
+
+    ctx.apply(cs, &operands, op)
+}
+
+pub fn bitand<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    apply_bitwise_op(cs, a, b, BitwiseOp::And, DEFAULT_BITWISE_CHUNK_WIDTH)
+}
+
+pub fn bitor<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    apply_bitwise_op(cs, a, b, BitwiseOp::Or, DEFAULT_BITWISE_CHUNK_WIDTH)
+}
+
+pub fn bitxor<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    apply_bitwise_op(cs, a, b, BitwiseOp::Xor, DEFAULT_BITWISE_CHUNK_WIDTH)
+}
+
+// decomposes a word into `num_flags` little-endian flags, all higher bits are enforced to be zero
+pub fn unpack_flags<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    word: &Num<E>,
+    num_flags: usize
+) -> Result<Vec<Boolean>, SynthesisError> {
+    assert!(num_flags < E::Fr::CAPACITY as usize);
+
+    let word_value = word.get_value().map(|el| fe_to_biguint(&el));
+    if let Num::Constant(..) = word {
+        let word_value = word_value.unwrap();
+        assert!(word_value.bits() as usize <= num_flags, "constant word has bits above the flags");
+        let flags = (0..num_flags).map(|i| Boolean::constant(word_value.bit(i as u64))).collect();
+
+        return Ok(flags);
+    }
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut lc = LinearCombination::zero();
+    lc.add_assign_number_with_coeff(word, minus_one);
+    let mut coeff = E::Fr::one();
+
+    let mut flags = Vec::with_capacity(num_flags);
+    for i in 0..num_flags {
+        let flag = Boolean::alloc(cs, word_value.as_ref().map(|v| v.bit(i as u64)))?;
+        lc.add_assign_boolean_with_coeff(&flag, coeff);
+        coeff.double();
+        flags.push(flag);
+    }
+    lc.enforce_zero(cs)?;
+
+    Ok(flags)
+}
+
+// enforces b = (2^width - 1) - a, i.e. b is a bitwise complement of a within `width` bits.
+// Complement doesn't borrow between limbs, so it's enforced limb by limb, the limb that contains
+// the top bit of the window is range checked, and limbs above the window must be zero in both
+pub fn enforce_bitwise_not<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>],
+    width: usize
+) -> Result<(), SynthesisError> {
+    let zero = Num::Constant(E::Fr::zero());
+    let num_limbs = std::cmp::max(a.len(), b.len());
+    assert!(width <= num_limbs * LIMB_WIDTH);
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    for i in 0..num_limbs {
+        let a_limb = a.get(i).unwrap_or(&zero);
+        let b_limb = b.get(i).unwrap_or(&zero);
+        let limb_width = std::cmp::min(width.saturating_sub(i * LIMB_WIDTH), LIMB_WIDTH);
+
+        if limb_width == 0 {
+            a_limb.assert_is_zero(cs)?;
+            b_limb.assert_is_zero(cs)?;
+            continue;
+        }
+        if limb_width < LIMB_WIDTH {
+            enforce_limb_width(cs, a_limb, limb_width)?;
+        }
+
+        let mask = (BigUint::from(1u64) << limb_width) - 1u64;
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_constant(biguint_to_fe(mask));
+        lc.add_assign_number_with_coeff(a_limb, minus_one);
+        lc.add_assign_number_with_coeff(b_limb, minus_one);
+        lc.enforce_zero(cs)?;
+    }
+
+    Ok(())
+}
+
+// computes a * b mod 2^(64 * n) where n is the number of limbs of a and b: only the columns
+// below n are accumulated and the carry out of the top one is range checked and dropped
+pub fn mul_wrapping<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert!(a.len() > 0 && a.len() == b.len());
+    let num_limbs = a.len();
+    let limb_max = (BigUint::from(1u64) << LIMB_WIDTH) - 1u64;
+
+    if a.iter().chain(b.iter()).all(|x| x.is_constant()) {
+        let product = get_limbs_value(a).unwrap() * get_limbs_value(b).unwrap();
+        let product = product % (BigUint::from(1u64) << (LIMB_WIDTH * num_limbs));
+        let limbs = split_into_fixed_number_of_limbs(product, LIMB_WIDTH, num_limbs);
+
+        return Ok(limbs.into_iter().map(|el| Num::Constant(biguint_to_fe(el))).collect());
+    }
+
+    let mut columns: Vec<LinearCombination<E>> = (0..num_limbs).map(|_| LinearCombination::zero()).collect();
+    let mut columns_max = vec![BigUint::from(0u64); num_limbs];
+    let mut columns_values = vec![Some(BigUint::from(0u64)); num_limbs];
+
+    let limb_max_squared = limb_max.clone() * &limb_max;
+
+    for (i, a_limb) in a.iter().enumerate() {
+        for (j, b_limb) in b[..(num_limbs - i)].iter().enumerate() {
+            match (a_limb, b_limb) {
+                (Num::Constant(c), x) | (x, Num::Constant(c)) => {
+                    columns[i + j].add_assign_number_with_coeff(x, *c);
+                    columns_max[i + j] += limb_max.clone() * fe_to_biguint(c);
+                },
+                _ => {
+                    let product = a_limb.mul(cs, b_limb)?;
+                    columns[i + j].add_assign_number_with_coeff(&product, E::Fr::one());
+                    columns_max[i + j] += &limb_max_squared;
+                }
+            }
+            columns_values[i + j] = match (columns_values[i + j].take(), a_limb.get_value(), b_limb.get_value()) {
+                (Some(acc), Some(x), Some(y)) => Some(acc + fe_to_biguint(&x) * fe_to_biguint(&y)),
+                _ => None
+            };
+        }
+    }
+
+    normalize_columns_wrapping(cs, columns, columns_max, columns_values)
+}
+
+// full 512-bit product of two 256-bit numbers without any modular reduction,
+// all 8 output limbs are range checked. Thin wrapper over UInt256::mul_wide
+pub fn simple_mul_wide<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: [Num<E>; 4],
+    b: [Num<E>; 4]
+) -> Result<[Num<E>; 8], SynthesisError> {
+    use super::uint::UInt256;
+
+    let a = UInt256::from_range_checked_limbs(&a);
+    let b = UInt256::from_range_checked_limbs(&b);
+    let (low, high) = a.mul_wide(cs, &b)?;
+
+    let mut product = [Num::Constant(E::Fr::zero()); 8];
+    product[..4].copy_from_slice(low.get_limbs());
+    product[4..].copy_from_slice(high.get_limbs());
+
+    Ok(product)
+}
+
+// computes a * b mod m where modulus limbs are variables. Witnesses q and r, range checks them,
+// enforces a * b = q * m + r over the integers and r < m, the latter is what makes r canonical
+// (and also requires m to be nonzero). Returns an error if m = 0
+pub fn modmul<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>],
+    modulus: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert!(a.len() > 0 && b.len() > 0 && modulus.len() > 0);
+
+    // quotient may be as wide as the product for small moduli
+    let num_quotient_limbs = a.len() + b.len();
+    let (q_value, r_value) = match (get_limbs_value(a), get_limbs_value(b), get_limbs_value(modulus)) {
+        (Some(a), Some(b), Some(m)) => {
+            if m.is_zero() {
+                return Err(SynthesisError::DivisionByZero);
+            }
+            let product = a * b;
+            (Some(product.clone() / &m), Some(product % &m))
+        },
+        _ => (None, None)
+    };
+
+    let mut quotient = Vec::with_capacity(num_quotient_limbs);
+    for limb_value in split_some_into_fixed_number_of_limbs(q_value, LIMB_WIDTH, num_quotient_limbs).iter() {
+        quotient.push(Num::alloc(cs, some_biguint_to_fe(limb_value))?);
+    }
+
+    let mut remainder = Vec::with_capacity(modulus.len());
+    for limb_value in split_some_into_fixed_number_of_limbs(r_value, LIMB_WIDTH, modulus.len()).iter() {
+        remainder.push(Num::alloc(cs, some_biguint_to_fe(limb_value))?);
+    }
+
+    enforce_modmul(cs, a, b, modulus, &quotient, &remainder)?;
+
+    Ok(remainder)
+}
+
+// range checks quotient and remainder limbs and enforces a * b = q * m + r with r < m
+fn enforce_modmul<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>],
+    modulus: &[Num<E>],
+    quotient: &[Num<E>],
+    remainder: &[Num<E>]
+) -> Result<(), SynthesisError> {
+    for limb in quotient.iter().chain(remainder.iter()) {
+        enforce_limb_width(cs, limb, LIMB_WIDTH)?;
+    }
+
+    let remainder_is_reduced = less_than(cs, remainder, modulus)?;
+    Boolean::enforce_equal(cs, &remainder_is_reduced, &Boolean::constant(true))?;
+
+    let lhs = mul_accumulate(cs, &[(a.to_vec(), b.to_vec())], &[])?;
+    let rhs = mul_accumulate(cs, &[(quotient.to_vec(), modulus.to_vec())], &[remainder.to_vec()])?;
+    let zero = Num::Constant(E::Fr::zero());
+    for i in 0..std::cmp::max(lhs.len(), rhs.len()) {
+        lhs.get(i).unwrap_or(&zero).enforce_equal(cs, rhs.get(i).unwrap_or(&zero))?;
+    }
+
+    Ok(())
+}
+
+// reduces a modulo a constant modulus: witnesses a = q * m + r with r < m over the integers.
+// If `as_inputs` is set, quotient and remainder limbs are allocated as public inputs in this order.
+// Remainder has as many limbs as the modulus, and quotient as many as needed for a / modulus
+fn reduce_by_constant_modulus<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    modulus: &BigUint,
+    as_inputs: bool
+) -> Result<(Vec<Num<E>>, Vec<Num<E>>), SynthesisError> {
+    assert!(a.len() > 0);
+    assert!(!modulus.is_zero());
+
+    let modulus_bits = modulus.bits() as usize;
+    let num_remainder_limbs = (modulus_bits + LIMB_WIDTH - 1) / LIMB_WIDTH;
+    let modulus_limbs: Vec<Num<E>> = split_into_fixed_number_of_limbs(modulus.clone(), LIMB_WIDTH, num_remainder_limbs)
+        .into_iter().map(|el| Num::Constant(biguint_to_fe(el))).collect();
+
+    // q < 2^(64 * n) / m < 2^(64 * n - modulus_bits + 1)
+    let quotient_bits = (a.len() * LIMB_WIDTH + 1).saturating_sub(modulus_bits);
+    let num_quotient_limbs = std::cmp::max((quotient_bits + LIMB_WIDTH - 1) / LIMB_WIDTH, 1);
+
+    let a_value = get_limbs_value(a);
+    let q_value = a_value.as_ref().map(|v| v.clone() / modulus);
+    let r_value = a_value.map(|v| v % modulus);
+
+    let alloc_limb = |cs: &mut CS, value: Option<BigUint>| -> Result<Num<E>, SynthesisError> {
+        let fe = some_biguint_to_fe::<E::Fr>(&value);
+        let limb = if as_inputs {
+            Num::Variable(AllocatedNum::alloc_input(cs, || Ok(*fe.get()?))?)
+        } else {
+            Num::alloc(cs, fe)?
+        };
+        enforce_limb_width(cs, &limb, LIMB_WIDTH)?;
+
+        Ok(limb)
+    };
+
+    let mut quotient = Vec::with_capacity(num_quotient_limbs);
+    for limb_value in split_some_into_fixed_number_of_limbs(q_value, LIMB_WIDTH, num_quotient_limbs).into_iter() {
+        quotient.push(alloc_limb(cs, limb_value)?);
+    }
+
+    let mut remainder = Vec::with_capacity(num_remainder_limbs);
+    for limb_value in split_some_into_fixed_number_of_limbs(r_value, LIMB_WIDTH, num_remainder_limbs).into_iter() {
+        remainder.push(alloc_limb(cs, limb_value)?);
+    }
+
+    let remainder_is_reduced = less_than(cs, &remainder, &modulus_limbs)?;
+    Boolean::enforce_equal(cs, &remainder_is_reduced, &Boolean::constant(true))?;
+
+    let recomputed = mul_accumulate(cs, &[(quotient.clone(), modulus_limbs)], &[remainder.clone()])?;
+    let zero = Num::Constant(E::Fr::zero());
+    for i in 0..std::cmp::max(recomputed.len(), a.len()) {
+        recomputed.get(i).unwrap_or(&zero).enforce_equal(cs, a.get(i).unwrap_or(&zero))?;
+    }
+
+    Ok((quotient, remainder))
+}
+
+// reduces a modulo a constant modulus and exposes both quotient and remainder as public inputs.
+// Inputs are allocated in the following order: quotient limbs, then remainder limbs, both little-endian
+pub fn reduce_with_public_io<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    modulus: &BigUint
+) -> Result<(Vec<Num<E>>, Vec<Num<E>>), SynthesisError> {
+    reduce_by_constant_modulus(cs, a, modulus, true)
+}
+
+// reduces a modulo a constant modulus that is smaller than the field characteristic,
+// so the remainder can be packed into a single field element exactly
+pub fn reduce_to_single_num<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    modulus: &BigUint
+) -> Result<Num<E>, SynthesisError> {
+    assert!(modulus < &repr_to_biguint::<E::Fr>(&E::Fr::char()), "modulus must be smaller than the field");
+
+    let (_, remainder) = reduce_by_constant_modulus(cs, a, modulus, false)?;
+
+    pack_limbs(&remainder).into_num(cs)
+}
+
+// returns sum of limb_i * 2^(64 * i) as a single linear combination, it's only exact
+// if the value is known to be less than the field characteristic
+fn pack_limbs<E: Engine>(limbs: &[Num<E>]) -> LinearCombination<E> {
+    let shift = biguint_to_fe::<E::Fr>(BigUint::from(1u64) << LIMB_WIDTH);
+    let mut lc = LinearCombination::zero();
+    let mut coeff = E::Fr::one();
+    for limb in limbs.iter() {
+        lc.add_assign_number_with_coeff(limb, coeff);
+        coeff.mul_assign(&shift);
+    }
+
+    lc
+}
+
+// decomposes a field element into four range checked 64-bit limbs. Four limbs can hold both x and x + p,
+// so the decomposition is also enforced to be less than the field modulus to be unique
+pub fn from_num<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    value: &Num<E>
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let num_limbs = 4;
+    assert!(E::Fr::NUM_BITS as usize <= num_limbs * LIMB_WIDTH);
+
+    let value_bn = value.get_value().map(|el| fe_to_biguint(&el));
+    let limbs_values = split_some_into_fixed_number_of_limbs(value_bn, LIMB_WIDTH, num_limbs);
+
+    if let Num::Constant(..) = value {
+        return Ok(limbs_values.into_iter().map(|el| Num::Constant(biguint_to_fe(el.unwrap()))).collect());
+    }
+
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+    let shift = biguint_to_fe::<E::Fr>(BigUint::from(1u64) << LIMB_WIDTH);
+
+    let mut lc = LinearCombination::zero();
+    lc.add_assign_number_with_coeff(value, minus_one);
+    let mut coeff = E::Fr::one();
+
+    let mut limbs = Vec::with_capacity(num_limbs);
+    for limb_value in limbs_values.iter() {
+        let limb = Num::alloc(cs, some_biguint_to_fe(limb_value))?;
+        enforce_limb_width(cs, &limb, LIMB_WIDTH)?;
+        lc.add_assign_number_with_coeff(&limb, coeff);
+        coeff.mul_assign(&shift);
+        limbs.push(limb);
+    }
+    lc.enforce_zero(cs)?;
+
+    let modulus = repr_to_biguint::<E::Fr>(&E::Fr::char());
+    let modulus_limbs: Vec<Num<E>> = split_into_fixed_number_of_limbs(modulus, LIMB_WIDTH, num_limbs)
+        .into_iter().map(|el| Num::Constant(biguint_to_fe(el))).collect();
+    let is_canonical = less_than(cs, &limbs, &modulus_limbs)?;
+    Boolean::enforce_equal(cs, &is_canonical, &Boolean::constant(true))?;
+
+    Ok(limbs)
+}
+
+// enforces that limbs recombine into the given native field element. This only makes sense if the
+// value fits into the field, so the witness is checked for that and the error is returned otherwise.
+// If limbs are wide enough to hold x + p, they are also enforced to be less than p in circuit,
+// otherwise recombination is only unique modulo p
+pub fn enforce_equal_to_num<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    limbs: &[Num<E>],
+    expected: &Num<E>
+) -> Result<(), SynthesisError> {
+    use crate::plonk::circuit::bigint_new::compute_shifts;
+
+    assert!(limbs.len().saturating_sub(1) * LIMB_WIDTH < E::Fr::CAPACITY as usize);
+    if let Some(value) = get_limbs_value(limbs) {
+        if value >= repr_to_biguint::<E::Fr>(&E::Fr::char()) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        if let Some(expected) = expected.get_value() {
+            debug_assert_eq!(biguint_to_fe::<E::Fr>(value), expected);
+        }
+    }
+
+    let shifts = compute_shifts::<E::Fr>();
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut lc = LinearCombination::zero();
+    for (i, limb) in limbs.iter().enumerate() {
+        lc.add_assign_number_with_coeff(limb, shifts[i * LIMB_WIDTH]);
+    }
+    lc.add_assign_number_with_coeff(expected, minus_one);
+    lc.enforce_zero(cs)?;
+
+    if limbs.len() * LIMB_WIDTH > E::Fr::CAPACITY as usize {
+        let modulus = repr_to_biguint::<E::Fr>(&E::Fr::char());
+        let modulus_limbs: Vec<Num<E>> = split_into_fixed_number_of_limbs(modulus, LIMB_WIDTH, limbs.len())
+            .into_iter().map(|el| Num::Constant(biguint_to_fe(el))).collect();
+        let is_canonical = less_than(cs, limbs, &modulus_limbs)?;
+        Boolean::enforce_equal(cs, &is_canonical, &Boolean::constant(true))?;
+    }
+
+    Ok(())
+}
+
+// hashes limbs with a caller provided gadget and enforces the result to be equal to the commitment
+pub fn enforce_limb_commitment<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    limbs: &[Num<E>],
+    committed_hash: &Num<E>,
+    hasher: impl Fn(&mut CS, &[Num<E>]) -> Result<Num<E>, SynthesisError>
+) -> Result<(), SynthesisError> {
+    let hash = hasher(cs, limbs)?;
+    hash.enforce_equal(cs, committed_hash)
+}
+
+// enforces n = a^2 + b^2 over the integers
+pub fn enforce_sum_of_two_squares<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    n: &[Num<E>],
+    a: &[Num<E>],
+    b: &[Num<E>]
+) -> Result<(), SynthesisError> {
+    let sum = mul_accumulate(cs, &[(a.to_vec(), a.to_vec()), (b.to_vec(), b.to_vec())], &[])?;
+
+    let zero = Num::Constant(E::Fr::zero());
+    for i in 0..std::cmp::max(sum.len(), n.len()) {
+        sum.get(i).unwrap_or(&zero).enforce_equal(cs, n.get(i).unwrap_or(&zero))?;
+    }
+
+    Ok(())
+}
+
+// packs little-endian bytes into 64-bit limbs, eight bytes per limb, every byte is range checked
+pub fn from_le_bytes<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    bytes: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert!(bytes.len() > 0);
+
+    for byte in bytes.iter() {
+        enforce_limb_width(cs, byte, BYTE_WIDTH)?;
+    }
+
+    let byte_shift = u64_to_fe::<E::Fr>(1u64 << BYTE_WIDTH);
+    let mut limbs = Vec::with_capacity((bytes.len() + BYTES_PER_LIMB - 1) / BYTES_PER_LIMB);
+    for chunk in bytes.chunks(BYTES_PER_LIMB) {
+        let mut lc = LinearCombination::zero();
+        let mut coeff = E::Fr::one();
+        for byte in chunk.iter() {
+            lc.add_assign_number_with_coeff(byte, coeff);
+            coeff.mul_assign(&byte_shift);
+        }
+
+        limbs.push(lc.into_num(cs)?);
+    }
+
+    Ok(limbs)
+}
+
+// decomposes every 64-bit limb into eight little-endian bytes, each range checked to 8 bits,
+// and enforces that bytes recompose into the limb
+pub fn into_le_bytes<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let byte_shift = u64_to_fe::<E::Fr>(1u64 << BYTE_WIDTH);
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut bytes = Vec::with_capacity(a.len() * BYTES_PER_LIMB);
+    for limb in a.iter() {
+        let limb_value = limb.get_value().map(|el| fe_to_biguint(&el));
+        let bytes_values = split_some_into_fixed_number_of_limbs(limb_value, BYTE_WIDTH, BYTES_PER_LIMB);
+
+        if let Num::Constant(..) = limb {
+            bytes.extend(bytes_values.into_iter().map(|el| Num::Constant(biguint_to_fe(el.unwrap()))));
+            continue;
+        }
+
+        let mut lc = LinearCombination::zero();
+        lc.add_assign_number_with_coeff(limb, minus_one);
+        let mut coeff = E::Fr::one();
+        for byte_value in bytes_values.iter() {
+            let byte = Num::alloc(cs, some_biguint_to_fe(byte_value))?;
+            enforce_limb_width(cs, &byte, BYTE_WIDTH)?;
+            lc.add_assign_number_with_coeff(&byte, coeff);
+            coeff.mul_assign(&byte_shift);
+            bytes.push(byte);
+        }
+        lc.enforce_zero(cs)?;
+    }
+
+    Ok(bytes)
+}
+
+// enforces number mod 97 = check_digits. This is the ISO 7064 MOD 97-10 remainder check, so for IBAN
+// validation the number is the rearranged IBAN and check_digits must be the constant 1, as the check
+// digits are already a part of the number and a valid IBAN is exactly the one with remainder 1
+pub fn enforce_mod97_checksum<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    number: &[Num<E>],
+    check_digits: &Num<E>
+) -> Result<(), SynthesisError> {
+    let remainder = reduce_by_small_prime_table(cs, number, 97)?;
+    remainder.enforce_equal(cs, check_digits)
+}
+
+// enforces a + b = 0 mod 2^k, i.e. b is the additive inverse of a in wrapping k-bit arithmetic
+pub fn enforce_additive_inverse_mod_pow2<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    b: &[Num<E>],
+    k: usize
+) -> Result<(), SynthesisError> {
+    assert!(k > 0);
+    let sum = mul_accumulate(cs, &[], &[a.to_vec(), b.to_vec()])?;
+    let low = mod_pow2(cs, &sum, k)?;
+    for limb in low.iter() {
+        limb.assert_is_zero(cs)?;
+    }
+
+    Ok(())
+}
+
+// computes base^e mod m by square-and-multiply over little-endian exponent bits, starting from the top bit.
+// Every step squares and multiplies, and the multiplication is kept or dropped by a select, so the
+// circuit shape only depends on the exponent length and takes exactly 2 * exponent_bits.len() modmuls
+pub fn modpow<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    base: &[Num<E>],
+    exponent_bits: &[Boolean],
+    modulus: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert!(exponent_bits.len() > 0);
+
+    let mut result: Vec<Num<E>> = (0..modulus.len()).map(|_| Num::Constant(E::Fr::zero())).collect();
+    result[0] = Num::Constant(E::Fr::one());
+
+    for bit in exponent_bits.iter().rev() {
+        let squared = modmul(cs, &result, &result, modulus)?;
+        let multiplied = modmul(cs, &squared, base, modulus)?;
+        result = conditionally_select(cs, bit, &multiplied, &squared)?;
+    }
+
+    Ok(result)
+}
+
+// computes a^-1 mod m: the inverse is witnessed out of circuit and range checked to be less than m,
+// soundness comes from enforcing a * inv = 1 mod m with modmul. Returns an error if m = 0, a = 0 mod m
+// or if a is not invertible at all
+pub fn modinv<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    modulus: &[Num<E>]
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    use num_integer::Integer;
+
+    assert!(a.len() > 0 && modulus.len() > 0);
+
+    let inv_value = match (get_limbs_value(a), get_limbs_value(modulus)) {
+        (Some(a), Some(m)) => {
+            if m.is_zero() {
+                return Err(SynthesisError::DivisionByZero);
+            }
+            let a = a % &m;
+            if a.is_zero() {
+                return Err(SynthesisError::DivisionByZero);
+            }
+            if a.gcd(&m) != BigUint::from(1u64) {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            Some(mod_inverse(&a, &m))
+        },
+        _ => None
+    };
+
+    let mut inv = Vec::with_capacity(modulus.len());
+    for limb_value in split_some_into_fixed_number_of_limbs(inv_value, LIMB_WIDTH, modulus.len()).iter() {
+        let limb = Num::alloc(cs, some_biguint_to_fe(limb_value))?;
+        enforce_limb_width(cs, &limb, LIMB_WIDTH)?;
+        inv.push(limb);
+    }
+
+    let inv_is_reduced = less_than(cs, &inv, modulus)?;
+    Boolean::enforce_equal(cs, &inv_is_reduced, &Boolean::constant(true))?;
+
+    let product = modmul(cs, a, &inv, modulus)?;
+    product[0].enforce_equal(cs, &Num::Constant(E::Fr::one()))?;
+    for limb in product[1..].iter() {
+        limb.assert_is_zero(cs)?;
+    }
+
+    Ok(inv)
+}
+
+// reduces a wide value modulo a constant modulus while its 64-bit limbs arrive one at a time,
+// starting from the lowest one. Every new limb is folded in as limb * (2^(64 * i) mod m) on top of
+// the running partial value, so the width of the value is never needed upfront. The partial value
+// grows by a single limb per push and is only reduced once it gets twice as wide as the modulus,
+// while finalize performs the last reduction into the canonical remainder
+#[derive(Debug)]
+pub struct OnlineReducer<E: Engine> {
+    modulus: BigUint,
+    // 2^(64 * i) mod m for the next limb
+    power: BigUint,
+    accumulator: Vec<Num<E>>,
+}
+
+impl<E: Engine> OnlineReducer<E> {
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(modulus > &BigUint::from(1u64));
+
+        Self {
+            modulus: modulus.clone(),
+            power: BigUint::from(1u64),
+            accumulator: vec![],
+        }
+    }
+
+    fn num_modulus_limbs(&self) -> usize {
+        (self.modulus.bits() as usize + LIMB_WIDTH - 1) / LIMB_WIDTH
+    }
+
+    // limb is expected to be range checked to 64 bits by the caller
+    pub fn push_limb<CS: ConstraintSystem<E>>(&mut self, cs: &mut CS, limb: &Num<E>) -> Result<(), SynthesisError> {
+        let num_modulus_limbs = self.num_modulus_limbs();
+        let power_limbs: Vec<Num<E>> = split_into_fixed_number_of_limbs(self.power.clone(), LIMB_WIDTH, num_modulus_limbs)
+            .into_iter().map(|el| Num::Constant(biguint_to_fe(el))).collect();
+
+        let adds = if self.accumulator.is_empty() { vec![] } else { vec![self.accumulator.clone()] };
+        let mut sum = mul_accumulate(cs, &[(vec![limb.clone()], power_limbs)], &adds)?;
+        if sum.len() > 2 * num_modulus_limbs {
+            let (_, remainder) = reduce_by_constant_modulus(cs, &sum, &self.modulus, false)?;
+            sum = remainder;
+        }
+
+        self.accumulator = sum;
+        self.power = (self.power.clone() << LIMB_WIDTH) % &self.modulus;
+
+        Ok(())
+    }
+
+    // returns the value of all pushed limbs mod m, with as many limbs as the modulus
+    pub fn finalize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<Vec<Num<E>>, SynthesisError> {
+        if self.accumulator.is_empty() {
+            return Ok((0..self.num_modulus_limbs()).map(|_| Num::Constant(E::Fr::zero())).collect());
+        }
+
+        let (_, remainder) = reduce_by_constant_modulus(cs, &self.accumulator, &self.modulus, false)?;
+
+        Ok(remainder)
+    }
+}
+
+// allocates a value of at most `bits` bits as ceil(bits / 64) little-endian limbs, all limbs are
+// range checked to 64 bits except for the top one that is checked to the exact remaining width
+pub fn alloc_with_bit_width<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    value: Option<BigUint>,
+    bits: usize
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    assert!(bits > 0);
+    if let Some(v) = value.as_ref() {
+        assert!(v.bits() as usize <= bits, "value has {} bits, while expected at most {}", v.bits(), bits);
+    }
+
+    let num_limbs = make_multiple(bits, LIMB_WIDTH) / LIMB_WIDTH;
+    let top_limb_width = bits - (num_limbs - 1) * LIMB_WIDTH;
+
+    let mut limbs = Vec::with_capacity(num_limbs);
+    for (i, limb_value) in split_some_into_fixed_number_of_limbs(value, LIMB_WIDTH, num_limbs).iter().enumerate() {
+        let limb = Num::alloc(cs, some_biguint_to_fe(limb_value))?;
+        let width = if i == num_limbs - 1 { top_limb_width } else { LIMB_WIDTH };
+        enforce_limb_width(cs, &limb, width)?;
+        limbs.push(limb);
+    }
+
+    Ok(limbs)
+}
+
+// returns little-endian bits of a limbed value, every limb is unpacked into 64 constrained booleans,
+// constant limbs give constant booleans for free
+pub fn to_bits_le<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    limbs: &[Num<E>]
+) -> Result<Vec<Boolean>, SynthesisError> {
+    let mut bits = Vec::with_capacity(limbs.len() * LIMB_WIDTH);
+    for limb in limbs.iter() {
+        bits.extend(unpack_flags(cs, limb, LIMB_WIDTH)?);
+    }
+
+    Ok(bits)
+}
+
+const RESIDUE_TABLE_CHUNK_WIDTH: usize = 8;
+const RESIDUE_TABLE_MAX_PERIOD: usize = 256;
+
+lazy_static! {
+    static ref RESIDUE_TABLE_NAMES: std::sync::Mutex<std::collections::HashMap<u64, &'static str>> =
+        std::sync::Mutex::new(std::collections::HashMap::new());
+}
+
+// table names have to be static, so a name is allocated once per distinct prime and kept
+// for the lifetime of the process. Deriving the name from the prime guarantees that a table
+// found by name was built for the same prime
+fn residue_table_name(prime: u64) -> &'static str {
+    let mut names = RESIDUE_TABLE_NAMES.lock().unwrap();
+    *names.entry(prime).or_insert_with(|| {
+        Box::leak(format!("Small prime residue table for {}", prime).into_boxed_str())
+    })
+}
+
+// returns the multiplicative order of 2^8 mod p, position weights 2^(8k) mod p repeat with this period.
+// There is no period for even moduli, and orders above RESIDUE_TABLE_MAX_PERIOD would make the table
+// too large, None is returned in both cases
+fn residue_table_period(prime: u64) -> Option<usize> {
+    if prime < 3 || prime % 2 == 0 {
+        return None;
+    }
+    let p = BigUint::from(prime);
+    let chunk_weight = (BigUint::from(1u64) << RESIDUE_TABLE_CHUNK_WIDTH) % &p;
+
+    let mut weight = chunk_weight.clone();
+    let mut period = 1;
+    while weight != BigUint::from(1u64) {
+        weight = (weight * &chunk_weight) % &p;
+        period += 1;
+        if period > RESIDUE_TABLE_MAX_PERIOD {
+            return None;
+        }
+    }
+
+    Some(period)
+}
+
+// for a fixed odd prime p the table has the following columns: (x, k, x * 2^(8k) mod p) for every
+// byte x and position k < T, where T is the order of 2^8 mod p, so a lookup simultaneously range checks
+// a byte and maps its contribution into the residue ring. Chunk at position i uses the key i mod T,
+// so a single table of 256 * T rows (T is at most 256) serves inputs of any length for a given prime
+#[derive(Clone)]
+pub struct SmallPrimeResidueTable<E: Engine> {
+    table_entries: [Vec<E::Fr>; 3],
+    table_lookup_map: std::collections::HashMap<(E::Fr, E::Fr), E::Fr>,
+    prime: u64,
+    num_positions: usize,
+    name: &'static str,
+}
+
+impl<E: Engine> SmallPrimeResidueTable<E> {
+    // returns an error if the order of 2^8 mod p is too large for a table or doesn't exist
+    pub fn new(prime: u64) -> Result<Self, SynthesisError> {
+        let num_positions = residue_table_period(prime).ok_or(SynthesisError::Unsatisfiable)?;
+        let name = residue_table_name(prime);
+        let chunk_range = 1u64 << RESIDUE_TABLE_CHUNK_WIDTH;
+        let table_len = (chunk_range as usize) * num_positions;
+
+        let mut keys0 = Vec::with_capacity(table_len);
+        let mut keys1 = Vec::with_capacity(table_len);
+        let mut values = Vec::with_capacity(table_len);
+        let mut map = std::collections::HashMap::with_capacity(table_len);
+
+        let p = BigUint::from(prime);
+        let mut position_weight = BigUint::from(1u64) % &p;
+        for position in 0..num_positions {
+            for chunk in 0..chunk_range {
+                let residue = (BigUint::from(chunk) * &position_weight) % &p;
+
+                let x = u64_to_fe(chunk);
+                let k = u64_to_fe(position as u64);
+                let z = biguint_to_fe(residue);
+
+                keys0.push(x);
+                keys1.push(k);
+                values.push(z);
+                map.insert((x, k), z);
+            }
+            position_weight = (position_weight << RESIDUE_TABLE_CHUNK_WIDTH) % &p;
+        }
+
+        Ok(Self {
+            table_entries: [keys0, keys1, values],
+            table_lookup_map: map,
+            prime,
+            num_positions,
+            name,
+        })
+    }
+}
+
+impl<E: Engine> std::fmt::Debug for SmallPrimeResidueTable<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmallPrimeResidueTable")
+            .field("prime", &self.prime)
+            .field("num_positions", &self.num_positions)
+            .finish()
+    }
+}
+
+impl<E: Engine> LookupTableInternal<E> for SmallPrimeResidueTable<E> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn table_size(&self) -> usize {
+        (1 << RESIDUE_TABLE_CHUNK_WIDTH) * self.num_positions
+    }
+    fn num_keys(&self) -> usize {
+        2
+    }
+    fn num_values(&self) -> usize {
+        1
+    }
+    fn allows_combining(&self) -> bool {
+        true
+    }
+    fn get_table_values_for_polys(&self) -> Vec<Vec<E::Fr>> {
+        vec![self.table_entries[0].clone(), self.table_entries[1].clone(), self.table_entries[2].clone()]
+    }
+    fn table_id(&self) -> E::Fr {
+        table_id_from_string(self.name)
+    }
+    fn sort(&self, _values: &[E::Fr], _column: usize) -> Result<Vec<E::Fr>, SynthesisError> {
+        Err(SynthesisError::Unsatisfiable)
+    }
+    fn box_clone(&self) -> Box<dyn LookupTableInternal<E>> {
+        Box::from(self.clone())
+    }
+    fn column_is_trivial(&self, column_num: usize) -> bool {
+        assert!(column_num <= 2);
+        false
+    }
+
+    fn is_valid_entry(&self, keys: &[E::Fr], values: &[E::Fr]) -> bool {
+        assert!(keys.len() == self.num_keys());
+        assert!(values.len() == self.num_values());
+
+        if let Some(entry) = self.table_lookup_map.get(&(keys[0], keys[1])) {
+            return entry == &(values[0]);
+        }
+        false
+    }
+
+    fn query(&self, keys: &[E::Fr]) -> Result<Vec<E::Fr>, SynthesisError> {
+        assert!(keys.len() == self.num_keys());
+
+        if let Some(entry) = self.table_lookup_map.get(&(keys[0], keys[1])) {
+            return Ok(vec![*entry])
+        }
+
+        Err(SynthesisError::Unsatisfiable)
+    }
+}
+
+// table name (and so the table id) is derived from the prime, so every prime gets its own table
+pub fn inscribe_small_prime_residue_table<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    prime: u64
+) -> Result<Arc<LookupTableApplication<E>>, SynthesisError> {
+    let name = residue_table_name(prime);
+    if let Ok(existing) = cs.get_table(name) {
+        return Ok(existing);
+    }
+
+    let table = SmallPrimeResidueTable::new(prime)?;
+    let columns3 = vec![
+        PolyIdentifier::VariablesPolynomial(0),
+        PolyIdentifier::VariablesPolynomial(1),
+        PolyIdentifier::VariablesPolynomial(2)
+    ];
+
+    cs.add_table(LookupTableApplication::new(name, table, columns3, None, true))
+}
+
+// reduces a little-endian limbed number modulo a small prime: every limb is split into bytes,
+// each byte is looked up together with its position to get its contribution mod p,
+// and the (short) sum of residues is reduced once more with a witnessed quotient.
+// Table for the prime is created on first use. If the order of 2^8 mod p is too large for a table
+// (or p is even) the general reduction by a constant modulus is used instead
+pub fn reduce_by_small_prime_table<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &[Num<E>],
+    prime: u64
+) -> Result<Num<E>, SynthesisError> {
+    if prime == 0 {
+        return Err(SynthesisError::DivisionByZero);
+    }
+    let period = match residue_table_period(prime) {
+        Some(period) => period as u64,
+        None => {
+            let (_, remainder) = reduce_by_constant_modulus(cs, a, &BigUint::from(prime), false)?;
+            return Ok(remainder[0]);
+        }
+    };
+    let chunks_per_limb = LIMB_WIDTH / RESIDUE_TABLE_CHUNK_WIDTH;
+    let num_positions = a.len() * chunks_per_limb;
+    let table = inscribe_small_prime_residue_table(cs, prime)?;
+
+    let p = BigUint::from(prime);
+    let dummy = CS::get_dummy_variable();
+    let mut minus_one = E::Fr::one();
+    minus_one.negate();
+
+    let mut chunk_shift = E::Fr::one();
+    for _ in 0..RESIDUE_TABLE_CHUNK_WIDTH {
+        chunk_shift.double();
+    }
+
+    let mut residues_lc = LinearCombination::zero();
+    let mut residues_sum = Some(BigUint::from(0u64));
+    let mut position_weight = BigUint::from(1u64) % &p;
+    let mut position_idx = 0u64;
+
+    for limb in a.iter() {
+        if let Num::Constant(c) = limb {
+            let residue = (fe_to_biguint(c) * &position_weight) % &p;
+            residues_lc.add_assign_constant(biguint_to_fe(residue.clone()));
+            residues_sum = residues_sum.map(|s| s + residue);
+            for _ in 0..chunks_per_limb {
+                position_weight = (position_weight << RESIDUE_TABLE_CHUNK_WIDTH) % &p;
+                position_idx += 1;
+            }
+            continue;
+        }
+
+        let chunk_values = split_some_into_slices(limb.get_value(), RESIDUE_TABLE_CHUNK_WIDTH, chunks_per_limb);
+        let mut decomposition_lc = LinearCombination::zero();
+        decomposition_lc.add_assign_number_with_coeff(limb, minus_one);
+        let mut coeff = E::Fr::one();
+
+        for chunk_value in chunk_values.into_iter() {
+            let position = AllocatedNum::alloc_cnst(cs, u64_to_fe(position_idx % period))?;
+            let chunk = AllocatedNum::alloc(cs, || Ok(*chunk_value.get()?))?;
+            let residue_value = chunk_value.map(|x| (fe_to_biguint(&x) * &position_weight) % &p);
+            let residue_fe = some_biguint_to_fe::<E::Fr>(&residue_value);
+            let residue = AllocatedNum::alloc(cs, || Ok(*residue_fe.get()?))?;
+
+            let vars = [chunk.get_variable(), position.get_variable(), residue.get_variable(), dummy];
+            let (_, coeffs) = CS::MainGate::format_term(MainGateTerm::new(), dummy)?;
+
+            cs.begin_gates_batch_for_step()?;
+            cs.apply_single_lookup_gate(&vars[..table.width()], table.clone())?;
+            cs.new_gate_in_batch(&CS::MainGate::default(), &coeffs, &vars, &[])?;
+            cs.end_gates_batch_for_step()?;
+
+            decomposition_lc.add_assign_variable_with_coeff(&chunk, coeff);
+            coeff.mul_assign(&chunk_shift);
+
+            residues_lc.add_assign_variable_with_coeff(&residue, E::Fr::one());
+            residues_sum = match (residues_sum, residue_value) {
+                (Some(s), Some(r)) => Some(s + r),
+                _ => None
+            };
+            position_weight = (position_weight << RESIDUE_TABLE_CHUNK_WIDTH) % &p;
+            position_idx += 1;
+        }
+
+        decomposition_lc.enforce_zero(cs)?;
+    }
+
+    // sum of residues is at most num_positions * (p - 1), so the quotient is less than num_positions
+    let quotient_value = residues_sum.as_ref().map(|s| s.clone() / &p);
+    let remainder_value = residues_sum.as_ref().map(|s| s.clone() % &p);
+
+    let quotient = Num::alloc(cs, some_biguint_to_fe(&quotient_value))?;
+    let remainder = Num::alloc(cs, some_biguint_to_fe(&remainder_value))?;
+    enforce_at_most_constant(cs, &quotient, &BigUint::from(num_positions as u64))?;
+    enforce_at_most_constant(cs, &remainder, &(p.clone() - 1u64))?;
+
+    let mut minus_p = biguint_to_fe::<E::Fr>(p);
+    minus_p.negate();
+    residues_lc.add_assign_number_with_coeff(&quotient, minus_p);
+    residues_lc.add_assign_number_with_coeff(&remainder, minus_one);
+    residues_lc.enforce_zero(cs)?;
+
+    Ok(remainder)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::plonk::circuit::*;
+    use crate::bellman::plonk::better_better_cs::cs::*;
+    use crate::bellman::pairing::bn256::{Bn256, Fr};
+    use super::super::test_utils::*;
+
+    #[test]
+    fn test_from_nibbles_round_trip() {
+        let mut cs = init_cs();
+
+        let bytes = hex::decode("0123456789abcdeffedcba98765432100f1e2d3c4b5a69788796a5b4c3d2e1f0").unwrap();
+        let value = BigUint::from_bytes_be(&bytes);
+
+        let mut nibbles = vec![];
+        for byte in bytes.iter() {
+            for nibble in [byte >> 4, byte & 0x0f].iter() {
+                let n = Num::alloc(&mut cs, Some(Fr::from_str(&nibble.to_string()).unwrap())).unwrap();
+                nibbles.push(n);
+            }
+        }
+        assert_eq!(nibbles.len(), 64);
+
+        let limbs = from_nibbles(&mut cs, &nibbles).unwrap();
+        assert_eq!(limbs.len(), 4);
+        assert_eq!(get_limbs_value(&limbs).unwrap(), value);
+
+        let expected = split_into_fixed_number_of_limbs(value, LIMB_WIDTH, 4);
+        for (limb, expected) in limbs.iter().zip(expected.into_iter()) {
+            assert_eq!(fe_to_biguint(&limb.get_value().unwrap()), expected);
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_reduce_by_small_prime_table() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        // tables for different primes live in the same constraint system,
+        // while 2 and 523 have no residue table and fall back to the general reduction
+        let mut cs = init_cs();
+        let primes = [3u64, 97, 65537, (1u64 << 61) - 1, 2, 523];
+        for &prime in primes.iter() {
+            let limb_values: Vec<u64> = (0..4).map(|_| rng.gen()).collect();
+            let mut limbs = vec![];
+            for v in limb_values.iter() {
+                limbs.push(Num::alloc(&mut cs, Some(u64_to_fe(*v))).unwrap());
+            }
+            // mix in a constant limb as well
+            limbs[2] = Num::Constant(u64_to_fe(limb_values[2]));
+
+            let value = get_limbs_value(&limbs).unwrap();
+            let result = reduce_by_small_prime_table(&mut cs, &limbs, prime).unwrap();
+
+            assert_eq!(fe_to_biguint(&result.get_value().unwrap()), value % BigUint::from(prime));
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_mul_accumulate() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        for _ in 0..10 {
+            let mut cs = init_cs();
+
+            let values: Vec<BigUint> = (0..5).map(|_| random_biguint(rng, 4)).collect();
+            let limbs: Vec<Vec<Num<Bn256>>> = values.iter().map(|v| alloc_limbs(&mut cs, v, 4)).collect();
+
+            let muls = vec![(limbs[0].clone(), limbs[1].clone()), (limbs[2].clone(), limbs[3].clone())];
+            let adds = vec![limbs[4].clone()];
+            let result = mul_accumulate(&mut cs, &muls, &adds).unwrap();
+
+            let expected = values[0].clone() * &values[1] + values[2].clone() * &values[3] + &values[4];
+            assert_eq!(result.len(), 9);
+            assert_eq!(get_limbs_value(&result).unwrap(), expected);
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_sub_with_borrows() {
+        let mut cs = init_cs();
+
+        // 2^192 - 1 forces a borrow through every limb
+        let a_value = BigUint::from(1u64) << (LIMB_WIDTH * 3);
+        let b_value = BigUint::from(1u64);
+        let a = alloc_limbs(&mut cs, &a_value, 4);
+        let b = alloc_limbs(&mut cs, &b_value, 4);
+
+        let (diff, borrows) = sub_with_borrows(&mut cs, &a, &b).unwrap();
+        assert_eq!(get_limbs_value(&diff).unwrap(), a_value.clone() - &b_value);
+        let borrows: Vec<_> = borrows.iter().map(|b| b.get_value().unwrap()).collect();
+        assert_eq!(borrows, vec![true, true, true, false]);
+
+        // and now underflow
+        let (diff, borrows) = sub_with_borrows(&mut cs, &b, &a).unwrap();
+        let expected = (BigUint::from(1u64) << (LIMB_WIDTH * 4)) + &b_value - &a_value;
+        assert_eq!(get_limbs_value(&diff).unwrap(), expected);
+        assert!(borrows.last().unwrap().get_value().unwrap());
+
+        // 0 - 1 borrows through every limb including the top one
+        let zero = alloc_limbs(&mut cs, &BigUint::from(0u64), 4);
+        let (diff, borrows) = sub_with_borrows(&mut cs, &zero, &b).unwrap();
+        assert_eq!(get_limbs_value(&diff).unwrap(), (BigUint::from(1u64) << (LIMB_WIDTH * 4)) - 1u64);
+        let borrows: Vec<_> = borrows.iter().map(|b| b.get_value().unwrap()).collect();
+        assert_eq!(borrows, vec![true, true, true, true]);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_mul_wrapping() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        let modulus = BigUint::from(1u64) << (LIMB_WIDTH * 4);
+
+        let a_value = random_biguint(rng, 4);
+        let b_value = random_biguint(rng, 4);
+        let a = alloc_limbs(&mut cs, &a_value, 4);
+        let b = alloc_limbs(&mut cs, &b_value, 4);
+        let mut c = alloc_limbs(&mut cs, &b_value, 4);
+        c[1] = Num::Constant(biguint_to_fe(split_into_fixed_number_of_limbs(b_value.clone(), LIMB_WIDTH, 4)[1].clone()));
+
+        let n = cs.get_current_step_number();
+        let product = mul_wrapping(&mut cs, &a, &b).unwrap();
+        let wrapping_gates = cs.get_current_step_number() - n;
+        assert_eq!(get_limbs_value(&product).unwrap(), (a_value.clone() * &b_value) % &modulus);
+
+        let product = mul_wrapping(&mut cs, &a, &c).unwrap();
+        assert_eq!(get_limbs_value(&product).unwrap(), (a_value.clone() * &b_value) % &modulus);
+
+        // only the low half of the columns is accumulated and normalized
+        let n = cs.get_current_step_number();
+        mul_accumulate(&mut cs, &[(a.clone(), b.clone())], &[]).unwrap();
+        assert!(wrapping_gates < cs.get_current_step_number() - n);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_add_with_carries() {
+        let mut cs = init_cs();
+
+        // 2^256 - 1 + 1 carries through every limb including the top one
+        let max_value = (BigUint::from(1u64) << (LIMB_WIDTH * 4)) - 1u64;
+        let a = alloc_limbs(&mut cs, &max_value, 4);
+        let b = alloc_limbs(&mut cs, &BigUint::from(1u64), 4);
+
+        let (sum, carries) = add_with_carries(&mut cs, &a, &b).unwrap();
+        assert!(get_limbs_value(&sum).unwrap().is_zero());
+        let carries: Vec<_> = carries.iter().map(|c| c.get_value().unwrap()).collect();
+        assert_eq!(carries, vec![true, true, true, true]);
+
+        // no carry out of the top limb
+        let c_value = BigUint::from(0xdeadbeefu64) << 100;
+        let c = alloc_limbs(&mut cs, &c_value, 4);
+        let (sum, carries) = add_with_carries(&mut cs, &c, &b).unwrap();
+        assert_eq!(get_limbs_value(&sum).unwrap(), c_value + 1u64);
+        assert!(!carries.last().unwrap().get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_minimal_limbs() {
+        let mut cs = init_cs();
+
+        let value = (BigUint::from(1u64) << 129) + BigUint::from(12345u64);
+        let limbs = alloc_limbs(&mut cs, &value, 4);
+        enforce_minimal_limbs(&mut cs, &limbs, 130).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_minimal_limbs_with_nonzero_top_limb() {
+        let mut cs = init_cs();
+
+        // top limb is nonzero, witnesses are computed as if it was zero
+        let claimed = BigUint::from(12345u64);
+        let value = (BigUint::from(1u64) << 192) + &claimed;
+        let limbs = alloc_forged_limbs(&mut cs, &value, &claimed, 4);
+        enforce_minimal_limbs(&mut cs, &limbs, 130).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_equals_mod_pow2() {
+        let mut cs = init_cs();
+
+        let low = BigUint::from(0xdeadbeefu64) + (BigUint::from(0x1234u64) << LIMB_WIDTH);
+        let a_value = low.clone() + (BigUint::from(1u64) << 100);
+        let b_value = low.clone() + (BigUint::from(3u64) << 150);
+        let a = alloc_limbs(&mut cs, &a_value, 3);
+        let b = alloc_limbs(&mut cs, &b_value, 3);
+
+        for &(k, expected) in [(64, true), (80, true), (100, true), (101, false), (160, false)].iter() {
+            let result = equals_mod_pow2(&mut cs, &a, &b, k).unwrap();
+            assert_eq!(result.get_value().unwrap(), expected, "for k = {}", k);
+        }
+
+        let reduced = mod_pow2(&mut cs, &a, 100).unwrap();
+        assert_eq!(reduced.len(), 2);
+        assert_eq!(get_limbs_value(&reduced).unwrap(), low);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_debit() {
+        let mut cs = init_cs();
+
+        let before_value = (BigUint::from(7u64) << 100) + BigUint::from(5u64);
+        let amount_value = BigUint::from(6u64) + (BigUint::from(1u64) << 70);
+        let after_value = before_value.clone() - &amount_value;
+
+        let before = alloc_limbs(&mut cs, &before_value, 2);
+        let amount = alloc_limbs(&mut cs, &amount_value, 2);
+        let after = alloc_limbs(&mut cs, &after_value, 2);
+
+        enforce_debit(&mut cs, &before, &after, &amount).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_debit_rejects_overdraft() {
+        let mut cs = init_cs();
+
+        // balance of 5 is debited by 6 leaving 1, witnesses are computed as if the balance was 7
+        let before = alloc_forged_limbs(&mut cs, &BigUint::from(5u64), &BigUint::from(7u64), 2);
+        let amount = alloc_limbs(&mut cs, &BigUint::from(6u64), 2);
+        let after = alloc_limbs(&mut cs, &BigUint::from(1u64), 2);
+
+        enforce_debit(&mut cs, &before, &after, &amount).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_sub_signed() {
+        let mut cs = init_cs();
+
+        let x_value = (BigUint::from(3u64) << 70) + BigUint::from(11u64);
+        let y_value = BigUint::from(42u64) << 64;
+        let x = alloc_limbs(&mut cs, &x_value, 2);
+        let y = alloc_limbs(&mut cs, &y_value, 2);
+
+        let (magnitude, sign) = sub_signed(&mut cs, &x, &y).unwrap();
+        assert_eq!(get_limbs_value(&magnitude).unwrap(), x_value.clone() - &y_value);
+        assert!(!sign.get_value().unwrap());
+
+        let (magnitude, sign) = sub_signed(&mut cs, &y, &x).unwrap();
+        assert_eq!(get_limbs_value(&magnitude).unwrap(), x_value.clone() - &y_value);
+        assert!(sign.get_value().unwrap());
+
+        let (magnitude, sign) = sub_signed(&mut cs, &x, &x).unwrap();
+        assert!(get_limbs_value(&magnitude).unwrap().is_zero());
+        assert!(!sign.get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_signed_digit_decomposition() {
+        let mut cs = init_cs();
+
+        let base = 16u64;
+        let value_bn = (BigUint::from(1u64) << 70) + BigUint::from(0xfedcba9u64);
+        let value = alloc_limbs(&mut cs, &value_bn, 2);
+
+        // recode into digits from [-8, 8)
+        let mut digits = vec![];
+        let mut tmp = (1u128 << 70) + 0xfedcba9u128;
+        let mut carry = 0u64;
+        for _ in 0..20 {
+            let d = (tmp % (base as u128)) as u64 + carry;
+            tmp /= base as u128;
+            let (digit, negative) = if d >= base / 2 { (base - d, true) } else { (d, false) };
+            carry = if negative { 1 } else { 0 };
+            let mut fe = u64_to_fe::<Fr>(digit);
+            if negative {
+                fe.negate();
+            }
+            digits.push(Num::alloc(&mut cs, Some(fe)).unwrap());
+        }
+        assert_eq!(carry, 0);
+        assert!(digits.iter().any(|d| fe_to_biguint(&d.get_value().unwrap()).bits() > 64));
+
+        enforce_signed_digit_decomposition(&mut cs, &value, &digits, base).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_signed_digit_decomposition_for_odd_base() {
+        let base = 5u64;
+        let to_fe = |d: i64| -> Fr {
+            let mut fe = u64_to_fe::<Fr>(d.abs() as u64);
+            if d < 0 {
+                fe.negate();
+            }
+            fe
+        };
+
+        // digits are in [-2, 2], both ends are allowed: 17 = 2 - 2 * 5 + 1 * 25
+        let mut cs = init_cs();
+        let value = alloc_limbs(&mut cs, &BigUint::from(17u64), 1);
+        let digits: Vec<_> = [2i64, -2, 1].iter().map(|&d| Num::alloc(&mut cs, Some(to_fe(d))).unwrap()).collect();
+        enforce_signed_digit_decomposition(&mut cs, &value, &digits, base).unwrap();
+        assert!(cs.is_satisfied());
+
+        // 3 = ceil(base/2) is out of the digit range
+        let mut cs = init_cs();
+        let value = alloc_limbs(&mut cs, &BigUint::from(3u64), 1);
+        let digits = vec![Num::alloc(&mut cs, Some(to_fe(3))).unwrap()];
+        enforce_signed_digit_decomposition(&mut cs, &value, &digits, base).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_div_by_constant() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        for &d in [1u64, 3, 10, (1u64 << 32) + 15, u64::max_value()].iter() {
+            let mut cs = init_cs();
+
+            let value = random_biguint(rng, 3);
+            let a = alloc_limbs(&mut cs, &value, 3);
+            let (q, r) = div_by_constant(&mut cs, &a, d).unwrap();
+
+            assert_eq!(get_limbs_value(&q).unwrap(), value.clone() / BigUint::from(d));
+            assert_eq!(fe_to_biguint(&r.get_value().unwrap()), value % BigUint::from(d));
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_enforce_nonce_advance() {
+        let mut cs = init_cs();
+
+        let old_value = (BigUint::from(1u64) << LIMB_WIDTH) - 1u64;
+        let old = alloc_limbs(&mut cs, &old_value, 4);
+
+        let next = alloc_limbs(&mut cs, &(old_value.clone() + 1u64), 4);
+        enforce_nonce_advance(&mut cs, &next, &old).unwrap();
+
+        let far = alloc_limbs(&mut cs, &(old_value.clone() << 150), 4);
+        enforce_nonce_advance(&mut cs, &far, &old).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_nonce_advance_rejects_replay() {
+        let mut cs = init_cs();
+
+        // the old nonce is reused, witnesses are computed as if it was advanced by one
+        let value = BigUint::from(1234567u64) << 100;
+        let old = alloc_limbs(&mut cs, &value, 4);
+        let new = alloc_forged_limbs(&mut cs, &value, &(value.clone() + 1u64), 4);
+
+        enforce_nonce_advance(&mut cs, &new, &old).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_masked_equal() {
+        let mut cs = init_cs();
+
+        let bytes = hex::decode("0123456789abcdeffedcba98765432100f1e2d3c4b5a69788796a5b4c3d2e1f0").unwrap();
+        let base = BigUint::from_bytes_be(&bytes);
+
+        // replace 32 bits crossing the boundary between the first and the second limb
+        let window_mask = BigUint::from(0xffffffffu64) << 48;
+        let updated = (base.clone() ^ (base.clone() & &window_mask)) + (BigUint::from(0xcafebabeu64) << 48);
+        assert!(updated != base);
+
+        let a = alloc_limbs(&mut cs, &updated, 4);
+        enforce_masked_equal(&mut cs, &a, &base, 48, 80).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_masked_equal_outside_of_window() {
+        let mut cs = init_cs();
+
+        // bit 90 is changed, witnesses are computed as if the value was left intact
+        let base = BigUint::from(0x1234u64) << 100;
+        let updated = base.clone() + (BigUint::from(1u64) << 90);
+
+        let a = alloc_forged_limbs(&mut cs, &updated, &base, 4);
+        enforce_masked_equal(&mut cs, &a, &base, 48, 80).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_bit_reversed_limbs() {
+        let mut cs = init_cs();
+
+        let a: Vec<_> = (0..8u64).map(|i| Num::alloc(&mut cs, Some(u64_to_fe(100 + i))).unwrap()).collect();
+        let permutation = [0usize, 4, 2, 6, 1, 5, 3, 7];
+        let b: Vec<_> = permutation.iter().map(|&j| Num::alloc(&mut cs, a[j].get_value()).unwrap()).collect();
+
+        enforce_bit_reversed_limbs(&mut cs, &a, &b).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_is_zero() {
+        let mut cs = init_cs();
+
+        let zero = Num::Constant(Fr::zero());
+        let one = Num::Constant(Fr::one());
+        assert!(is_zero(&mut cs, &[zero.clone(), zero.clone()]).unwrap().get_constant_value());
+        assert!(!is_zero(&mut cs, &[zero.clone(), one.clone()]).unwrap().get_constant_value());
+
+        let var_zero = Num::alloc(&mut cs, Some(Fr::zero())).unwrap();
+        let var_nonzero = Num::alloc(&mut cs, Some(u64_to_fe(12345))).unwrap();
+
+        let flag = is_zero(&mut cs, &[var_zero.clone(), zero.clone(), var_zero.clone()]).unwrap();
+        assert!(!flag.is_constant());
+        assert!(flag.get_value().unwrap());
+
+        let flag = is_zero(&mut cs, &[var_zero.clone(), zero.clone(), var_nonzero.clone()]).unwrap();
+        assert!(!flag.get_value().unwrap());
+
+        // constant nonzero limb makes the whole thing constant false
+        let flag = is_zero(&mut cs, &[var_zero.clone(), one.clone()]).unwrap();
+        assert!(!flag.get_constant_value());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_equals() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        for _ in 0..10 {
+            let x_value = random_biguint(rng, 4);
+            let y_value = random_biguint(rng, 4);
+            let x = alloc_limbs(&mut cs, &x_value, 4);
+            let x_copy = alloc_limbs(&mut cs, &x_value, 4);
+            let y = alloc_limbs(&mut cs, &y_value, 4);
+
+            assert!(equals(&mut cs, &x, &x_copy).unwrap().get_value().unwrap());
+            assert_eq!(equals(&mut cs, &x, &y).unwrap().get_value().unwrap(), x_value == y_value);
+
+            let x_constant: Vec<_> = split_into_fixed_number_of_limbs(x_value.clone(), LIMB_WIDTH, 4).into_iter()
+                .map(|el| Num::Constant(biguint_to_fe(el))).collect();
+            assert!(equals(&mut cs, &x_constant, &x).unwrap().get_value().unwrap());
+            assert!(equals(&mut cs, &x_constant, &x_constant).unwrap().get_constant_value());
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_equals_gates_for_mixed_limbs() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0656]);
+
+        let mut cs = init_cs();
+        let value = random_biguint(rng, 4);
+        let x = alloc_limbs(&mut cs, &value, 4);
+        let mut y = alloc_limbs(&mut cs, &value, 4);
+        // constant pairs in the two low limbs, a variable pair and a variable-constant pair above
+        let mut x_mixed = x.clone();
+        for i in 0..2 {
+            x_mixed[i] = Num::Constant(x[i].get_value().unwrap());
+            y[i] = Num::Constant(y[i].get_value().unwrap());
+        }
+        y[3] = Num::Constant(y[3].get_value().unwrap());
+
+        let start = cs.get_current_step_number();
+        let constant_result = equals(&mut cs, &x_mixed[..2], &y[..2]).unwrap();
+        assert_eq!(cs.get_current_step_number(), start);
+        assert!(constant_result.get_constant_value());
+
+        let start = cs.get_current_step_number();
+        let mixed_result = equals(&mut cs, &x_mixed, &y).unwrap();
+        let mixed_gates = cs.get_current_step_number() - start;
+
+        let start = cs.get_current_step_number();
+        let variable_result = equals(&mut cs, &x_mixed[2..], &y[2..]).unwrap();
+        let variable_gates = cs.get_current_step_number() - start;
+
+        assert!(mixed_result.get_value().unwrap());
+        assert!(variable_result.get_value().unwrap());
+        assert_eq!(mixed_gates, variable_gates);
+
+        // a single differing constant limb makes the result false
+        let mut other_limb = y[0].get_value().unwrap();
+        other_limb.add_assign(&Fr::one());
+        y[0] = Num::Constant(other_limb);
+        assert!(!equals(&mut cs, &x_mixed, &y).unwrap().get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_reduce_to_field() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let modulus = repr_to_biguint::<Fr>(&Fr::char());
+        for &num_limbs in [1usize, 4, 6].iter() {
+            let mut cs = init_cs();
+
+            let value = random_biguint(rng, num_limbs);
+            let a = alloc_limbs(&mut cs, &value, num_limbs);
+            let result = reduce_to_field(&mut cs, &a).unwrap();
+
+            assert_eq!(result.get_value().unwrap(), biguint_to_fe::<Fr>(value % &modulus));
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_less_than() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let max_value = (BigUint::from(1u64) << (LIMB_WIDTH * 4)) - 1u64;
+        let zero = alloc_limbs(&mut cs, &BigUint::from(0u64), 4);
+        let max = alloc_limbs(&mut cs, &max_value, 4);
+        assert!(less_than(&mut cs, &zero, &max).unwrap().get_value().unwrap());
+        assert!(!less_than(&mut cs, &max, &zero).unwrap().get_value().unwrap());
+        assert!(!less_than(&mut cs, &max, &max).unwrap().get_value().unwrap());
+
+        for _ in 0..20 {
+            let x_value = random_biguint(rng, 4);
+            // make some of the high limbs equal, so that lower limbs decide
+            let y_value = if rng.gen() { random_biguint(rng, 4) } else { x_value.clone() ^ BigUint::from(rng.gen::<u64>()) };
+            let x = alloc_limbs(&mut cs, &x_value, 4);
+            let y = alloc_limbs(&mut cs, &y_value, 4);
+
+            assert_eq!(less_than(&mut cs, &x, &y).unwrap().get_value().unwrap(), x_value < y_value);
+            assert!(!less_than(&mut cs, &x, &x).unwrap().get_value().unwrap());
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_conditionally_select() {
+        let mut cs = init_cs();
+
+        let x_value = (BigUint::from(0xabcdefu64) << 130) + BigUint::from(17u64);
+        let y_value = (BigUint::from(0x123456u64) << 70) + BigUint::from(17u64);
+        let x = alloc_limbs(&mut cs, &x_value, 4);
+        let y = alloc_limbs(&mut cs, &y_value, 4);
+
+        for &flag_value in [true, false].iter() {
+            let flag = Boolean::alloc(&mut cs, Some(flag_value)).unwrap();
+            let result = conditionally_select(&mut cs, &flag, &x, &y).unwrap();
+            let expected = if flag_value { &x_value } else { &y_value };
+            assert_eq!(get_limbs_value(&result).unwrap(), *expected);
+        }
+
+        let before = cs.get_current_step_number();
+        let result = conditionally_select(&mut cs, &Boolean::constant(false), &x, &y).unwrap();
+        assert_eq!(get_limbs_value(&result).unwrap(), y_value);
+        assert_eq!(cs.get_current_step_number(), before);
+
+        let x_constant: Vec<_> = split_into_fixed_number_of_limbs(x_value.clone(), LIMB_WIDTH, 4).into_iter()
+            .map(|el| Num::Constant(biguint_to_fe(el))).collect();
+        let result = conditionally_select(&mut cs, &Boolean::constant(true), &x_constant, &y).unwrap();
+        assert!(result.iter().all(|limb| limb.is_constant()));
+        assert_eq!(get_limbs_value(&result).unwrap(), x_value);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_shifts() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        let modulus = BigUint::from(1u64) << (LIMB_WIDTH * 4);
+
+        let value = random_biguint(rng, 4);
+        let a = alloc_limbs(&mut cs, &value, 4);
+        for &bits in [0usize, 1, 17, 64, 100, 128, 191, 255, 256, 300].iter() {
+            let shifted_left = shl(&mut cs, &a, bits).unwrap();
+            assert_eq!(shifted_left.len(), 4);
+            assert_eq!(get_limbs_value(&shifted_left).unwrap(), (value.clone() << bits) % &modulus, "shl by {}", bits);
+
+            let shifted_right = shr(&mut cs, &a, bits).unwrap();
+            assert_eq!(shifted_right.len(), 4);
+            assert_eq!(get_limbs_value(&shifted_right).unwrap(), value.clone() >> bits, "shr by {}", bits);
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_bitwise_ops() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let x_value = random_biguint(rng, 4);
+        let y_value = random_biguint(rng, 4);
+        let x = alloc_limbs(&mut cs, &x_value, 4);
+        let mut y = alloc_limbs(&mut cs, &y_value, 4);
+        // one of the limbs is constant
+        y[1] = Num::Constant(y[1].get_value().unwrap());
+
+        let and = bitand(&mut cs, &x, &y).unwrap();
+        let or = bitor(&mut cs, &x, &y).unwrap();
+        let xor = bitxor(&mut cs, &x, &y).unwrap();
+
+        assert_eq!(get_limbs_value(&and).unwrap(), x_value.clone() & &y_value);
+        assert_eq!(get_limbs_value(&or).unwrap(), x_value.clone() | &y_value);
+        assert_eq!(get_limbs_value(&xor).unwrap(), x_value.clone() ^ &y_value);
+
+        for &chunk_width in [1usize, 2, 4, 8, 16].iter() {
+            let and = apply_bitwise_op(&mut cs, &x, &y, BitwiseOp::And, chunk_width).unwrap();
+            let xor = apply_bitwise_op(&mut cs, &x, &y, BitwiseOp::Xor, chunk_width).unwrap();
+            assert_eq!(get_limbs_value(&and).unwrap(), x_value.clone() & &y_value);
+            assert_eq!(get_limbs_value(&xor).unwrap(), x_value.clone() ^ &y_value);
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_bitwise_ctx_reuses_decomposition() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0655]);
+
+        let mut cs = init_cs();
+
+        let x_value = random_biguint(rng, 4);
+        let y_value = random_biguint(rng, 4);
+        let x = alloc_limbs(&mut cs, &x_value, 4);
+        let y = alloc_limbs(&mut cs, &y_value, 4);
+        let ctx = BitwiseCtx::new(&mut cs, DEFAULT_BITWISE_CHUNK_WIDTH).unwrap();
+
+        let start = cs.get_current_step_number();
+        let operands = ctx.decompose(&mut cs, &x, &y).unwrap();
+        let xor = ctx.bitxor(&mut cs, &operands).unwrap();
+        let and = ctx.bitand(&mut cs, &operands).unwrap();
+        let shared_gates = cs.get_current_step_number() - start;
+
+        assert_eq!(get_limbs_value(&xor).unwrap(), x_value.clone() ^ &y_value);
+        assert_eq!(get_limbs_value(&and).unwrap(), x_value.clone() & &y_value);
+
+        let start = cs.get_current_step_number();
+        bitxor(&mut cs, &x, &y).unwrap();
+        bitand(&mut cs, &x, &y).unwrap();
+        let separate_gates = cs.get_current_step_number() - start;
+
+        assert!(cs.is_satisfied());
+        assert!(shared_gates < separate_gates);
+    }
+
+    #[test]
+    fn test_unpack_flags() {
+        let mut cs = init_cs();
+
+        let status = 0b1010_0110u64;
+        let word = Num::alloc(&mut cs, Some(u64_to_fe(status))).unwrap();
+        let flags = unpack_flags(&mut cs, &word, 8).unwrap();
+        for (i, flag) in flags.iter().enumerate() {
+            assert_eq!(flag.get_value().unwrap(), (status >> i) & 1 == 1);
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_unpack_flags_with_high_bit_set() {
+        let mut cs = init_cs();
+
+        // bit 8 is set, flags are witnessed as if it was not
+        let word = alloc_forged_limbs(&mut cs, &BigUint::from(0b1_0000_0001u64), &BigUint::from(0b1u64), 1)[0];
+        unpack_flags(&mut cs, &word, 8).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_bitwise_not() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let width = 150;
+        let mask = (BigUint::from(1u64) << width) - 1u64;
+        let x_value = random_biguint(rng, 3) & &mask;
+        let y_value = mask.clone() - &x_value;
+        assert!((x_value.clone() & &y_value).is_zero());
+        assert_eq!(x_value.clone() | &y_value, mask);
+
+        let x = alloc_limbs(&mut cs, &x_value, 4);
+        let y = alloc_limbs(&mut cs, &y_value, 4);
+        enforce_bitwise_not(&mut cs, &x, &y, width).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_simple_mul_wide() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let max_value = (BigUint::from(1u64) << (LIMB_WIDTH * 4)) - 1u64;
+        let mut pairs = vec![(max_value.clone(), max_value.clone())];
+        for _ in 0..5 {
+            pairs.push((random_biguint(rng, 4), random_biguint(rng, 4)));
+        }
+
+        for (x_value, y_value) in pairs.into_iter() {
+            let mut cs = init_cs();
+
+            let x = alloc_limbs(&mut cs, &x_value, 4);
+            let y = alloc_limbs(&mut cs, &y_value, 4);
+            let x = [x[0].clone(), x[1].clone(), x[2].clone(), x[3].clone()];
+            let y = [y[0].clone(), y[1].clone(), y[2].clone(), y[3].clone()];
+
+            let product = simple_mul_wide(&mut cs, x, y).unwrap();
+            // compare over the integers, the product is wider than the field
+            assert_eq!(get_limbs_value(&product).unwrap(), x_value * y_value);
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_modmul_with_variable_modulus() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        // secp256k1 group order and a small odd modulus
+        let secp_n = BigUint::from_bytes_be(
+            &hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap()
+        );
+        for modulus_value in vec![secp_n, BigUint::from(1000003u64)].into_iter() {
+            let mut cs = init_cs();
+
+            let x_value = random_biguint(rng, 4) % &modulus_value;
+            let y_value = random_biguint(rng, 4) % &modulus_value;
+            let x = alloc_limbs(&mut cs, &x_value, 4);
+            let y = alloc_limbs(&mut cs, &y_value, 4);
+            let modulus = alloc_limbs(&mut cs, &modulus_value, 4);
+
+            let result = modmul(&mut cs, &x, &y, &modulus).unwrap();
+            assert_eq!(result.len(), 4);
+            assert_eq!(get_limbs_value(&result).unwrap(), (x_value * y_value) % &modulus_value);
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_modmul_rejects_unreduced_remainder() {
+        let mut cs = init_cs();
+
+        let modulus_value = BigUint::from(1000003u64);
+        let x_value = BigUint::from(987654u64);
+        let y_value = BigUint::from(123456u64);
+        let product = x_value.clone() * &y_value;
+        let q_value = product.clone() / &modulus_value;
+        let r_value = product % &modulus_value;
+
+        let x = alloc_limbs(&mut cs, &x_value, 1);
+        let y = alloc_limbs(&mut cs, &y_value, 1);
+        let modulus = alloc_limbs(&mut cs, &modulus_value, 1);
+
+        // a * b = (q - 1) * m + (r + m) holds over the integers, so only r < m is violated
+        let quotient = alloc_forged_limbs(&mut cs, &(q_value.clone() - 1u64), &q_value, 2);
+        let remainder = alloc_forged_limbs(&mut cs, &(r_value.clone() + &modulus_value), &r_value, 1);
+        enforce_modmul(&mut cs, &x, &y, &modulus, &quotient, &remainder).unwrap();
+        assert!(!cs.is_satisfied());
+
+        let zero = alloc_limbs(&mut cs, &BigUint::from(0u64), 1);
+        assert!(modmul(&mut cs, &x, &y, &zero).is_err());
+    }
+
+    #[test]
+    fn test_reduce_with_public_io() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let modulus = (BigUint::from(1u64) << 130) - BigUint::from(5u64);
+        let value = random_biguint(rng, 4);
+        let a = alloc_limbs(&mut cs, &value, 4);
+
+        let (q, r) = reduce_with_public_io(&mut cs, &a, &modulus).unwrap();
+        assert_eq!(q.len(), 2);
+        assert_eq!(r.len(), 3);
+        assert_eq!(get_limbs_value(&q).unwrap(), value.clone() / &modulus);
+        assert_eq!(get_limbs_value(&r).unwrap(), value % &modulus);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_from_num() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let mut minus_one = Fr::one();
+        minus_one.negate();
+        for value in vec![rng.gen::<Fr>(), minus_one, Fr::zero()].into_iter() {
+            let num = Num::alloc(&mut cs, Some(value)).unwrap();
+            let limbs = from_num(&mut cs, &num).unwrap();
+            assert_eq!(limbs.len(), 4);
+            assert_eq!(get_limbs_value(&limbs).unwrap(), fe_to_biguint(&value));
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_equal_to_num() {
+        let mut cs = init_cs();
+
+        let value = (BigUint::from(0x1234u64) << 130) + BigUint::from(77u64);
+        let limbs = alloc_limbs(&mut cs, &value, 4);
+        let num = Num::alloc(&mut cs, Some(biguint_to_fe(value))).unwrap();
+        enforce_equal_to_num(&mut cs, &limbs, &num).unwrap();
+
+        assert!(cs.is_satisfied());
+
+        // doesn't fit into the field
+        let too_large = (BigUint::from(1u64) << 255) + BigUint::from(77u64);
+        let limbs = alloc_limbs(&mut cs, &too_large, 4);
+        assert!(enforce_equal_to_num(&mut cs, &limbs, &num).is_err());
+    }
+
+    #[test]
+    fn test_enforce_equal_to_num_rejects_aliased_limbs() {
+        let mut cs = init_cs();
+
+        // limbs hold value + p, which recombines to the same field element
+        let value = BigUint::from(77u64);
+        let aliased = value.clone() + repr_to_biguint::<Fr>(&Fr::char());
+        let limbs = alloc_forged_limbs(&mut cs, &aliased, &value, 4);
+        let num = Num::alloc(&mut cs, Some(biguint_to_fe(value))).unwrap();
+
+        enforce_equal_to_num(&mut cs, &limbs, &num).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_limb_commitment() {
+        let mut cs = init_cs();
+
+        let limbs: Vec<_> = (1..=4u64).map(|i| Num::alloc(&mut cs, Some(u64_to_fe(i * 1000))).unwrap()).collect();
+        let commitment = Num::alloc(&mut cs, Some(u64_to_fe(10000))).unwrap();
+
+        let sum_hasher = |cs: &mut TrivialAssembly<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>, limbs: &[Num<Bn256>]| {
+            let mut lc = LinearCombination::zero();
+            for limb in limbs.iter() {
+                lc.add_assign_number_with_coeff(limb, Fr::one());
+            }
+            lc.into_num(cs)
+        };
+        enforce_limb_commitment(&mut cs, &limbs, &commitment, sum_hasher).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_sum_of_two_squares() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let x_value = random_biguint(rng, 2);
+        let y_value = random_biguint(rng, 2);
+        let n_value = x_value.clone() * &x_value + y_value.clone() * &y_value;
+
+        let x = alloc_limbs(&mut cs, &x_value, 2);
+        let y = alloc_limbs(&mut cs, &y_value, 2);
+        let n = alloc_limbs(&mut cs, &n_value, 5);
+        enforce_sum_of_two_squares(&mut cs, &n, &x, &y).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_sum_of_two_squares_with_bad_witness() {
+        let mut cs = init_cs();
+
+        // 21 is not a sum of two squares, try 4^2 + 2^2 = 20 instead
+        let n = alloc_forged_limbs(&mut cs, &BigUint::from(21u64), &BigUint::from(20u64), 1);
+        let x = alloc_limbs(&mut cs, &BigUint::from(4u64), 1);
+        let y = alloc_limbs(&mut cs, &BigUint::from(2u64), 1);
+        enforce_sum_of_two_squares(&mut cs, &n, &x, &y).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_reduce_to_single_num() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        // any modulus below the field characteristic works, take a 200 bit one
+        let modulus = (BigUint::from(1u64) << 200) - BigUint::from(75u64);
+        for &num_limbs in [2usize, 4, 5].iter() {
+            let mut cs = init_cs();
+
+            let value = random_biguint(rng, num_limbs);
+            let a = alloc_limbs(&mut cs, &value, num_limbs);
+            let result = reduce_to_single_num(&mut cs, &a, &modulus).unwrap();
+
+            assert_eq!(fe_to_biguint(&result.get_value().unwrap()), value % &modulus);
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_from_le_bytes_round_trip() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let raw: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let value = biguint_from_le_bytes(&raw);
+        assert_eq!(biguint_to_le_bytes(&value, 32), raw);
+        assert_eq!(biguint_to_le_bytes(&BigUint::from(0x0102u64), 4), vec![2u8, 1, 0, 0]);
+
+        let bytes: Vec<_> = raw.iter().map(|b| Num::alloc(&mut cs, Some(u64_to_fe(*b as u64))).unwrap()).collect();
+        let limbs = from_le_bytes(&mut cs, &bytes).unwrap();
+        assert_eq!(limbs.len(), 4);
+        assert_eq!(get_limbs_value(&limbs).unwrap(), value);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_into_le_bytes() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let value = random_biguint(rng, 4);
+        let a = alloc_limbs(&mut cs, &value, 4);
+        let bytes = into_le_bytes(&mut cs, &a).unwrap();
+        assert_eq!(bytes.len(), 32);
+
+        let raw: Vec<u8> = bytes.iter().map(|b| fe_to_biguint(&b.get_value().unwrap()).to_bytes_le()[0]).collect();
+        assert_eq!(biguint_from_le_bytes(&raw), value);
+
+        let limbs = from_le_bytes(&mut cs, &bytes).unwrap();
+        assert_eq!(get_limbs_value(&limbs).unwrap(), value);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_mod97_checksum() {
+        let mut cs = init_cs();
+
+        let value = BigUint::parse_bytes(b"3214282912345698765432161182", 10).unwrap();
+        let number = alloc_limbs(&mut cs, &value, 2);
+        let expected = (value % BigUint::from(97u64)).to_str_radix(10).parse::<u64>().unwrap();
+        // the number is the rearranged IBAN GB82 WEST 1234 5698 7654 32, so it is valid with remainder 1
+        assert_eq!(expected, 1);
+        let check_digits = Num::alloc(&mut cs, Some(u64_to_fe(expected))).unwrap();
+
+        enforce_mod97_checksum(&mut cs, &number, &check_digits).unwrap();
+        assert!(cs.is_satisfied());
+
+    }
+
+    #[test]
+    fn test_enforce_mod97_checksum_with_wrong_digits() {
+        let mut cs = init_cs();
+
+        let value = BigUint::parse_bytes(b"3214282912345698765432161182", 10).unwrap();
+        let number = alloc_limbs(&mut cs, &value, 2);
+        let expected = (value % BigUint::from(97u64)).to_str_radix(10).parse::<u64>().unwrap();
+        // the gadget sees the correct remainder, while the circuit holds a wrong one
+        let check_digits = match Num::alloc(&mut cs, Some(u64_to_fe(expected + 1))).unwrap() {
+            Num::Variable(mut var) => {
+                var.value = Some(u64_to_fe(expected));
+                Num::Variable(var)
+            },
+            Num::Constant(..) => unreachable!()
+        };
+
+        enforce_mod97_checksum(&mut cs, &number, &check_digits).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_additive_inverse_mod_pow2() {
+        let mut cs = init_cs();
+        use rand::{XorShiftRng, SeedableRng};
+        let mut rng = XorShiftRng::from_seed([0x5dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        for &k in [64usize, 100, 192].iter() {
+            let modulus = BigUint::from(1u64) << k;
+            let a_value = random_biguint(&mut rng, 3) % &modulus;
+            let b_value = (modulus.clone() - &a_value) % &modulus;
+
+            let a = alloc_limbs(&mut cs, &a_value, 3);
+            let b = alloc_limbs(&mut cs, &b_value, 3);
+            enforce_additive_inverse_mod_pow2(&mut cs, &a, &b, k).unwrap();
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_enforce_additive_inverse_mod_pow2_off_by_one() {
+        let mut cs = init_cs();
+
+        let k = 100;
+        let a_value = BigUint::from(0xdeadbeefu64) << 40;
+        let inverse = (BigUint::from(1u64) << k) - &a_value;
+        let off_by_one = inverse.clone() + 1u64;
+
+        let a = alloc_limbs(&mut cs, &a_value, 2);
+        let b = alloc_forged_limbs(&mut cs, &off_by_one, &inverse, 2);
+        enforce_additive_inverse_mod_pow2(&mut cs, &a, &b, k).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_modpow() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let mut cs = init_cs();
+        let mut rng = XorShiftRng::from_seed([0x2dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let modulus_value = random_biguint(&mut rng, 2) | BigUint::from(1u64);
+        let base_value = random_biguint(&mut rng, 2) % &modulus_value;
+        let exponent: u32 = rng.gen::<u32>() >> 12;
+
+        let base = alloc_limbs(&mut cs, &base_value, 2);
+        let modulus = alloc_limbs(&mut cs, &modulus_value, 2);
+        let exponent_bits: Vec<Boolean> = (0..20).map(|i| {
+            Boolean::alloc(&mut cs, Some((exponent >> i) & 1 == 1)).unwrap()
+        }).collect();
+
+        let result = modpow(&mut cs, &base, &exponent_bits, &modulus).unwrap();
+        let expected = base_value.modpow(&BigUint::from(exponent), &modulus_value);
+        assert_eq!(get_limbs_value(&result).unwrap(), expected);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_modinv() {
+        use rand::{XorShiftRng, SeedableRng};
+        let mut cs = init_cs();
+        let mut rng = XorShiftRng::from_seed([0x1dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        // 2^127 - 1 is prime
+        let modulus_value = (BigUint::from(1u64) << 127) - 1u64;
+        let modulus = alloc_limbs(&mut cs, &modulus_value, 2);
+
+        for _ in 0..4 {
+            let a_value = random_biguint(&mut rng, 2);
+            let a = alloc_limbs(&mut cs, &a_value, 2);
+
+            let inv = modinv(&mut cs, &a, &modulus).unwrap();
+            let inv_value = get_limbs_value(&inv).unwrap();
+            assert!(inv_value < modulus_value);
+            assert_eq!(a_value * inv_value % &modulus_value, BigUint::from(1u64));
+        }
+
+        assert!(cs.is_satisfied());
+
+        let zero = alloc_limbs(&mut cs, &modulus_value, 2);
+        assert!(modinv(&mut cs, &zero, &modulus).is_err());
+
+        let zero_modulus = alloc_limbs(&mut cs, &BigUint::from(0u64), 2);
+        let a = alloc_limbs(&mut cs, &BigUint::from(3u64), 2);
+        match modinv(&mut cs, &a, &zero_modulus) {
+            Err(SynthesisError::DivisionByZero) => {},
+            _ => panic!("expected division by zero")
+        }
+    }
+
+    #[test]
+    fn test_online_reducer() {
+        use rand::{XorShiftRng, SeedableRng};
+        let mut cs = init_cs();
+        let mut rng = XorShiftRng::from_seed([0x0dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let modulus = random_biguint(&mut rng, 2) | BigUint::from(1u64);
+        let value = random_biguint(&mut rng, 8);
+        let limbs = alloc_limbs(&mut cs, &value, 8);
+
+        let mut reducer = OnlineReducer::new(&modulus);
+        for limb in limbs.iter() {
+            reducer.push_limb(&mut cs, limb).unwrap();
+        }
+        let streamed = reducer.finalize(&mut cs).unwrap();
+        assert_eq!(streamed.len(), 2);
+
+        let (_, batched) = reduce_by_constant_modulus(&mut cs, &limbs, &modulus, false).unwrap();
+        assert_eq!(get_limbs_value(&streamed).unwrap(), get_limbs_value(&batched).unwrap());
+        assert_eq!(get_limbs_value(&streamed).unwrap(), value % &modulus);
+
+        let empty = OnlineReducer::<Bn256>::new(&modulus).finalize(&mut cs).unwrap();
+        assert_eq!(empty.len(), 2);
+        assert!(get_limbs_value(&empty).unwrap().is_zero());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_to_bits_le() {
+        use rand::{XorShiftRng, SeedableRng};
+        let mut cs = init_cs();
+        let mut rng = XorShiftRng::from_seed([0x7dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let value = random_biguint(&mut rng, 4);
+        let mut limbs = alloc_limbs(&mut cs, &value, 4);
+        let constant_limb_value = split_into_fixed_number_of_limbs(value.clone(), LIMB_WIDTH, 4)[2].clone();
+        limbs[2] = Num::Constant(biguint_to_fe(constant_limb_value));
+
+        let bits = to_bits_le(&mut cs, &limbs).unwrap();
+        assert_eq!(bits.len(), 256);
+        assert!(bits[128..192].iter().all(|b| b.is_constant()));
+
+        let mut recomposed = BigUint::from(0u64);
+        for bit in bits.iter().rev() {
+            recomposed <<= 1;
+            if bit.get_value().unwrap() {
+                recomposed += 1u64;
+            }
+        }
+        assert_eq!(recomposed, value);
+
+        assert!(cs.is_satisfied());
+    }
+}
@@ -43,6 +43,11 @@ pub mod range_constraint_gate;
 pub mod range_constraint_functions;
 pub mod range_constraint_with_two_bit_gate;
 pub mod single_table_range_constraint;
+pub mod limbed_uint;
+pub mod uint;
+
+#[cfg(test)]
+mod test_utils;
 
 pub use self::bigint::*;
 pub use self::field::*;
@@ -108,7 +113,9 @@ pub fn split_into_slices<F: PrimeField>(
         }
     }
     else {
-        let it = repr.as_ref().iter().map(|x| u64_to_fe::<F>(*x)).take(num_slices);
+        // slice may span several words of the representation, so go through BigUint
+        let it = split_into_fixed_number_of_limbs(fe_to_biguint(el), slice_width, num_slices).into_iter()
+            .map(|x| biguint_to_fe::<F>(x));
         slices.extend(it);
     };
 
@@ -128,6 +135,28 @@ pub fn split_some_into_slices<F: PrimeField>(
     }
 }
 
+// splits an element of at most `num_bits` bits into slices of `slice_width` bits and reports
+// the width of every slice, so the last one has just the remaining width
+pub fn split_into_slices_with_widths<F: PrimeField>(
+    el: &F,
+    slice_width: usize,
+    num_bits: usize
+) -> Vec<(F, usize)> {
+    assert!(slice_width > 0 && num_bits > 0);
+    let num_slices = (num_bits + slice_width - 1) / slice_width;
+    let slices = split_into_slices(el, slice_width, num_slices);
+
+    let mut result = Vec::with_capacity(num_slices);
+    let mut remaining = num_bits;
+    for slice in slices.into_iter() {
+        let width = std::cmp::min(slice_width, remaining);
+        remaining -= width;
+        result.push((slice, width));
+    }
+
+    result
+}
+
 fn split_into_accululating_slices<F: PrimeField>(
     el: &F,
     slice_width: usize,
@@ -490,4 +519,55 @@ mod test {
             None
         ).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_split_into_wide_slices() {
+        use crate::bellman::pairing::bn256::Fr;
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let el: Fr = rng.gen();
+        let value = fe_to_biguint(&el);
+
+        for &width in [64usize, 96, 100].iter() {
+            let num_slices = (Fr::NUM_BITS as usize + width - 1) / width + 1;
+            let slices = split_into_slices(&el, width, num_slices);
+            let new_slices = crate::plonk::circuit::bigint_new::split_into_slices(&el, width, num_slices);
+            assert_eq!(slices, new_slices);
+            assert_eq!(slices.len(), num_slices);
+
+            let mut recomposed = num_bigint::BigUint::from(0u64);
+            for slice in slices.iter().rev() {
+                let slice = fe_to_biguint(slice);
+                assert!(slice.bits() as usize <= width);
+                recomposed <<= width;
+                recomposed += slice;
+            }
+            assert_eq!(recomposed, value, "for width {}", width);
+        }
+    }
+
+    #[test]
+    fn test_split_into_slices_with_widths() {
+        use crate::bellman::pairing::bn256::Fr;
+
+        let el = Fr::from_str("123456789123456789123456789").unwrap();
+        let value = fe_to_biguint(&el);
+        let num_bits = value.bits() as usize;
+
+        for &width in [8usize, 16, 17, 64, 70].iter() {
+            let slices = split_into_slices_with_widths(&el, width, num_bits);
+            assert_eq!(slices.iter().map(|(_, w)| *w).sum::<usize>(), num_bits);
+            assert!(slices[..slices.len() - 1].iter().all(|(_, w)| *w == width));
+
+            let mut recomposed = num_bigint::BigUint::from(0u64);
+            for (slice, w) in slices.iter().rev() {
+                let slice = fe_to_biguint(slice);
+                assert!(slice.bits() as usize <= *w);
+                recomposed <<= *w;
+                recomposed += slice;
+            }
+            assert_eq!(recomposed, value);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::bellman::pairing::bn256::Bn256;
+use crate::bellman::plonk::better_better_cs::cs::{TrivialAssembly, Width4MainGateWithDNext};
+
+use crate::plonk::circuit::Width4WithCustomGates;
+use crate::plonk::circuit::allocated_num::Num;
+use crate::plonk::circuit::tables::inscribe_default_range_table_for_bit_width_over_first_three_columns;
+
+use super::bigint::{biguint_to_fe, split_into_fixed_number_of_limbs};
+use super::limbed_uint::LIMB_WIDTH;
+
+use num_bigint::BigUint;
+
+// helpers shared by the tests of limbed numbers
+
+pub(crate) fn init_cs() -> TrivialAssembly<Bn256, Width4WithCustomGates, Width4MainGateWithDNext> {
+    let mut cs = TrivialAssembly::<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>::new();
+    inscribe_default_range_table_for_bit_width_over_first_three_columns(&mut cs, 16).unwrap();
+
+    cs
+}
+
+pub(crate) fn alloc_limbs(cs: &mut TrivialAssembly<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>, value: &BigUint, num_limbs: usize) -> Vec<Num<Bn256>> {
+    split_into_fixed_number_of_limbs(value.clone(), LIMB_WIDTH, num_limbs).into_iter().map(|el| {
+        Num::alloc(cs, Some(biguint_to_fe(el))).unwrap()
+    }).collect()
+}
+
+// allocates limbs holding the `actual` value in the constraint system, while gadgets see `claimed`
+// when computing their witnesses. This is a witness a dishonest prover could produce, and unlike honest
+// witnesses it doesn't trip the asserts in the helpers, so constraints themselves get checked
+pub(crate) fn alloc_forged_limbs(
+    cs: &mut TrivialAssembly<Bn256, Width4WithCustomGates, Width4MainGateWithDNext>,
+    actual: &BigUint,
+    claimed: &BigUint,
+    num_limbs: usize
+) -> Vec<Num<Bn256>> {
+    let claimed_limbs = split_into_fixed_number_of_limbs(claimed.clone(), LIMB_WIDTH, num_limbs);
+    alloc_limbs(cs, actual, num_limbs).into_iter().zip(claimed_limbs.into_iter()).map(|(limb, claimed)| {
+        match limb {
+            Num::Variable(mut var) => {
+                var.value = Some(biguint_to_fe(claimed));
+                Num::Variable(var)
+            },
+            Num::Constant(..) => unreachable!()
+        }
+    }).collect()
+}
+
+pub(crate) fn random_biguint<R: rand::Rng>(rng: &mut R, num_limbs: usize) -> BigUint {
+    let mut result = BigUint::from(0u64);
+    for _ in 0..num_limbs {
+        result <<= LIMB_WIDTH;
+        result += BigUint::from(rng.gen::<u64>());
+    }
+
+    result
+}
@@ -0,0 +1,709 @@
+use crate::bellman::pairing::{
+    Engine,
+};
+
+use crate::bellman::pairing::ff::Field;
+
+use crate::bellman::{
+    SynthesisError,
+};
+
+use crate::bellman::plonk::better_better_cs::cs::ConstraintSystem;
+
+use crate::plonk::circuit::allocated_num::Num;
+use crate::plonk::circuit::boolean::Boolean;
+
+use super::bigint::*;
+use super::limbed_uint::*;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+pub const UINT256_NUM_LIMBS: usize = 4;
+
+// unsigned integer as LIMBS little-endian 64-bit limbs on top of the limbed_uint helpers.
+// Limbs are range checked once when the value is allocated, so operations don't check inputs again.
+// Arithmetic wraps modulo 2^(64 * LIMBS)
+#[derive(Debug)]
+pub struct UIntN<E: Engine, const LIMBS: usize> {
+    pub(crate) limbs: [Num<E>; LIMBS],
+}
+
+pub type UInt256<E> = UIntN<E, UINT256_NUM_LIMBS>;
+
+impl<E: Engine, const LIMBS: usize> Clone for UIntN<E, LIMBS> {
+    fn clone(&self) -> Self {
+        Self {
+            limbs: self.limbs
+        }
+    }
+}
+
+impl<E: Engine, const LIMBS: usize> Copy for UIntN<E, LIMBS> {}
+
+impl<E: Engine, const LIMBS: usize> UIntN<E, LIMBS> {
+    pub fn alloc<CS: ConstraintSystem<E>>(cs: &mut CS, value: Option<BigUint>) -> Result<Self, SynthesisError> {
+        if let Some(v) = value.as_ref() {
+            assert!(v.bits() as usize <= LIMBS * LIMB_WIDTH, "value doesn't fit into {} limbs", LIMBS);
+        }
+
+        let mut limbs = [Num::Constant(E::Fr::zero()); LIMBS];
+        let limb_values = split_some_into_fixed_number_of_limbs(value, LIMB_WIDTH, LIMBS);
+        for (limb, limb_value) in limbs.iter_mut().zip(limb_values.iter()) {
+            *limb = Num::alloc(cs, some_biguint_to_fe(limb_value))?;
+            enforce_limb_width(cs, limb, LIMB_WIDTH)?;
+        }
+
+        Ok(Self { limbs })
+    }
+
+    pub fn from_biguint(value: &BigUint) -> Self {
+        assert!(value.bits() as usize <= LIMBS * LIMB_WIDTH, "value doesn't fit into {} limbs", LIMBS);
+
+        let mut limbs = [Num::Constant(E::Fr::zero()); LIMBS];
+        let limb_values = split_into_fixed_number_of_limbs(value.clone(), LIMB_WIDTH, LIMBS);
+        for (limb, limb_value) in limbs.iter_mut().zip(limb_values.into_iter()) {
+            *limb = Num::Constant(biguint_to_fe(limb_value));
+        }
+
+        Self { limbs }
+    }
+
+    // limbs are expected to be range checked to 64 bits
+    pub(crate) fn from_range_checked_limbs(limbs: &[Num<E>]) -> Self {
+        assert_eq!(limbs.len(), LIMBS);
+
+        let mut result = [Num::Constant(E::Fr::zero()); LIMBS];
+        result.copy_from_slice(limbs);
+
+        Self { limbs: result }
+    }
+
+    pub fn get_limbs(&self) -> &[Num<E>; LIMBS] {
+        &self.limbs
+    }
+
+    pub fn get_value(&self) -> Option<BigUint> {
+        get_limbs_value(&self.limbs)
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.limbs.iter().all(|el| el.is_constant())
+    }
+
+    // a + b mod 2^(64 * LIMBS), the final carry is dropped
+    pub fn add<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let (sum, _) = self.add_checked(cs, other)?;
+
+        Ok(sum)
+    }
+
+    // a + b mod 2^(64 * LIMBS) together with the final carry, which is set iff a + b >= 2^(64 * LIMBS)
+    pub fn add_checked<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<(Self, Boolean), SynthesisError> {
+        let (sum, carries) = add_with_carries(cs, &self.limbs, &other.limbs)?;
+
+        Ok((Self::from_range_checked_limbs(&sum), *carries.last().unwrap()))
+    }
+
+    // a - b mod 2^(64 * LIMBS), the final borrow is dropped
+    pub fn sub<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let (difference, _) = sub_with_borrows(cs, &self.limbs, &other.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&difference))
+    }
+
+    // a - b mod 2^(64 * LIMBS) together with the final borrow, which is set iff a < b
+    pub fn sub_checked<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<(Self, Boolean), SynthesisError> {
+        let (difference, borrows) = sub_with_borrows(cs, &self.limbs, &other.limbs)?;
+
+        Ok((Self::from_range_checked_limbs(&difference), *borrows.last().unwrap()))
+    }
+
+    // a * b mod 2^(64 * LIMBS), only the low half of the product is computed
+    pub fn mul<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let product = mul_wrapping(cs, &self.limbs, &other.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&product))
+    }
+
+    // full product of a and b as (low, high) halves
+    pub fn mul_wide<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<(Self, Self), SynthesisError> {
+        let product = mul_accumulate(cs, &[(self.limbs.to_vec(), other.limbs.to_vec())], &[])?;
+        assert_eq!(product.len(), 2 * LIMBS);
+
+        Ok((
+            Self::from_range_checked_limbs(&product[..LIMBS]),
+            Self::from_range_checked_limbs(&product[LIMBS..])
+        ))
+    }
+
+    // returns (q, r) such that a = q * b + r and r < b, the latter also enforces b to be nonzero
+    pub fn div<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<(Self, Self), SynthesisError> {
+        let (q_value, r_value) = match (self.get_value(), other.get_value()) {
+            (Some(a), Some(b)) => {
+                if b.is_zero() {
+                    return Err(SynthesisError::DivisionByZero);
+                }
+                (Some(a.clone() / &b), Some(a % &b))
+            },
+            _ => (None, None)
+        };
+
+        let quotient = Self::alloc(cs, q_value)?;
+        let remainder = Self::alloc(cs, r_value)?;
+
+        let remainder_is_reduced = less_than(cs, &remainder.limbs, &other.limbs)?;
+        Boolean::enforce_equal(cs, &remainder_is_reduced, &Boolean::constant(true))?;
+
+        let recomputed = mul_accumulate(cs, &[(quotient.limbs.to_vec(), other.limbs.to_vec())], &[remainder.limbs.to_vec()])?;
+        let zero = Num::Constant(E::Fr::zero());
+        for (i, limb) in recomputed.iter().enumerate() {
+            limb.enforce_equal(cs, self.limbs.get(i).unwrap_or(&zero))?;
+        }
+
+        Ok((quotient, remainder))
+    }
+
+    pub fn is_zero<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Boolean, SynthesisError> {
+        super::limbed_uint::is_zero(cs, &self.limbs)
+    }
+
+    pub fn equals<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Boolean, SynthesisError> {
+        super::limbed_uint::equals(cs, &self.limbs, &other.limbs)
+    }
+
+    // returns a < b as the final borrow of a - b
+    pub fn less_than<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Boolean, SynthesisError> {
+        super::limbed_uint::less_than(cs, &self.limbs, &other.limbs)
+    }
+
+    // returns a if flag is set and b otherwise, every limb takes at most a single gate
+    pub fn conditionally_select<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        flag: &Boolean,
+        a: &Self,
+        b: &Self
+    ) -> Result<Self, SynthesisError> {
+        let selected = super::limbed_uint::conditionally_select(cs, flag, &a.limbs, &b.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&selected))
+    }
+
+    pub fn bitand<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let result = super::limbed_uint::bitand(cs, &self.limbs, &other.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&result))
+    }
+
+    pub fn bitor<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let result = super::limbed_uint::bitor(cs, &self.limbs, &other.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&result))
+    }
+
+    pub fn bitxor<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self) -> Result<Self, SynthesisError> {
+        let result = super::limbed_uint::bitxor(cs, &self.limbs, &other.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&result))
+    }
+
+    // computes a * b mod m, returns an error if m = 0
+    pub fn modmul<CS: ConstraintSystem<E>>(&self, cs: &mut CS, other: &Self, modulus: &Self) -> Result<Self, SynthesisError> {
+        let result = super::limbed_uint::modmul(cs, &self.limbs, &other.limbs, &modulus.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&result))
+    }
+
+    // computes self^e mod m for little-endian exponent bits, takes 2 * exponent_bits.len() modmuls
+    pub fn modpow<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        exponent_bits: &[Boolean],
+        modulus: &Self
+    ) -> Result<Self, SynthesisError> {
+        let result = super::limbed_uint::modpow(cs, &self.limbs, exponent_bits, &modulus.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&result))
+    }
+
+    // computes self^-1 mod m, returns an error if m = 0, self = 0 mod m or if it's not invertible at all
+    pub fn modinv<CS: ConstraintSystem<E>>(&self, cs: &mut CS, modulus: &Self) -> Result<Self, SynthesisError> {
+        let inv = super::limbed_uint::modinv(cs, &self.limbs, &modulus.limbs)?;
+
+        Ok(Self::from_range_checked_limbs(&inv))
+    }
+
+    // returns 64 * LIMBS little-endian bits, constant limbs give constant booleans for free
+    pub fn to_bits_le<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<Vec<Boolean>, SynthesisError> {
+        super::limbed_uint::to_bits_le(cs, &self.limbs)
+    }
+}
+
+// conversions that only make sense for 256-bit values: the native field element fits into four limbs,
+// and the value is exactly 32 bytes
+impl<E: Engine> UInt256<E> {
+    // decomposes a field element into four range checked limbs, the decomposition is enforced to be canonical
+    pub fn from_num<CS: ConstraintSystem<E>>(cs: &mut CS, value: &Num<E>) -> Result<Self, SynthesisError> {
+        let limbs = super::limbed_uint::from_num(cs, value)?;
+
+        Ok(Self::from_range_checked_limbs(&limbs))
+    }
+
+    // enforces the value to be equal to a native field element, the value must fit into the field
+    pub fn enforce_equal_to_num<CS: ConstraintSystem<E>>(&self, cs: &mut CS, expected: &Num<E>) -> Result<(), SynthesisError> {
+        super::limbed_uint::enforce_equal_to_num(cs, &self.limbs, expected)
+    }
+
+    // packs 32 little-endian bytes into limbs, every byte is range checked to 8 bits
+    pub fn from_le_bytes<CS: ConstraintSystem<E>>(cs: &mut CS, bytes: &[Num<E>; 32]) -> Result<Self, SynthesisError> {
+        let limbs = super::limbed_uint::from_le_bytes(cs, &bytes[..])?;
+
+        Ok(Self::from_range_checked_limbs(&limbs))
+    }
+
+    // decomposes the value into 32 little-endian bytes, each range checked to 8 bits
+    pub fn into_le_bytes<CS: ConstraintSystem<E>>(&self, cs: &mut CS) -> Result<[Num<E>; 32], SynthesisError> {
+        let bytes = super::limbed_uint::into_le_bytes(cs, &self.limbs)?;
+
+        let mut result = [Num::Constant(E::Fr::zero()); 32];
+        result.copy_from_slice(&bytes);
+
+        Ok(result)
+    }
+}
+
+// unsigned integer of a bit width only known at runtime as ceil(bits / 64) little-endian 64-bit limbs. All
+// limbs are range checked to 64 bits except for the top one, which is checked to the exact remaining width
+#[derive(Clone, Debug)]
+pub struct GenericUInt<E: Engine> {
+    pub(crate) limbs: Vec<Num<E>>,
+    pub(crate) top_limb_width: usize,
+}
+
+impl<E: Engine> GenericUInt<E> {
+    pub fn with_bit_width<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        value: Option<BigUint>,
+        bits: usize
+    ) -> Result<Self, SynthesisError> {
+        let limbs = alloc_with_bit_width(cs, value, bits)?;
+        let top_limb_width = bits - (limbs.len() - 1) * LIMB_WIDTH;
+
+        Ok(Self { limbs, top_limb_width })
+    }
+
+    pub fn get_limbs(&self) -> &[Num<E>] {
+        &self.limbs
+    }
+
+    pub fn get_value(&self) -> Option<BigUint> {
+        get_limbs_value(&self.limbs)
+    }
+
+    pub fn top_limb_width(&self) -> usize {
+        self.top_limb_width
+    }
+
+    pub fn bit_width(&self) -> usize {
+        (self.limbs.len() - 1) * LIMB_WIDTH + self.top_limb_width
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bellman::plonk::better_better_cs::cs::*;
+    use crate::bellman::pairing::bn256::Bn256;
+    use super::super::test_utils::*;
+
+    fn check_uintn_against_biguint<R: rand::Rng, const LIMBS: usize>(rng: &mut R) {
+        let mut cs = init_cs();
+        let modulus = BigUint::from(1u64) << (LIMBS * LIMB_WIDTH);
+
+        for _ in 0..4 {
+            let a_value = random_biguint(rng, LIMBS);
+            let b_value = random_biguint(rng, LIMBS) >> rng.gen_range(0, LIMBS * LIMB_WIDTH);
+            let a = UIntN::<Bn256, LIMBS>::alloc(&mut cs, Some(a_value.clone())).unwrap();
+            let b = UIntN::<Bn256, LIMBS>::alloc(&mut cs, Some(b_value.clone())).unwrap();
+
+            let (sum, carry) = a.add_checked(&mut cs, &b).unwrap();
+            assert_eq!(sum.get_value().unwrap(), (a_value.clone() + &b_value) % &modulus);
+            assert_eq!(carry.get_value().unwrap(), a_value.clone() + &b_value >= modulus);
+
+            let (difference, borrow) = b.sub_checked(&mut cs, &a).unwrap();
+            assert_eq!(difference.get_value().unwrap(), (modulus.clone() + &b_value - &a_value) % &modulus);
+            assert_eq!(borrow.get_value().unwrap(), b_value < a_value);
+
+            let product = a.mul(&mut cs, &b).unwrap();
+            assert_eq!(product.get_value().unwrap(), (a_value.clone() * &b_value) % &modulus);
+
+            let (low, high) = a.mul_wide(&mut cs, &b).unwrap();
+            assert_eq!(low.get_value().unwrap(), (a_value.clone() * &b_value) % &modulus);
+            assert_eq!(high.get_value().unwrap(), (a_value.clone() * &b_value) / &modulus);
+
+            if !b_value.is_zero() {
+                let (quotient, remainder) = a.div(&mut cs, &b).unwrap();
+                assert_eq!(quotient.get_value().unwrap(), a_value.clone() / &b_value);
+                assert_eq!(remainder.get_value().unwrap(), a_value % &b_value);
+            }
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uintn_against_biguint() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x8dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        check_uintn_against_biguint::<_, 2>(rng);
+        check_uintn_against_biguint::<_, 4>(rng);
+        check_uintn_against_biguint::<_, 6>(rng);
+        check_uintn_against_biguint::<_, 8>(rng);
+    }
+
+    #[test]
+    fn test_uint256_arithmetic() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        let modulus = BigUint::from(1u64) << 256;
+
+        for _ in 0..4 {
+            let a_value = random_biguint(rng, 4);
+            let b_value = random_biguint(rng, 2);
+
+            let a = UInt256::alloc(&mut cs, Some(a_value.clone())).unwrap();
+            let b = UInt256::alloc(&mut cs, Some(b_value.clone())).unwrap();
+            let c = UInt256::<Bn256>::from_biguint(&b_value);
+            assert!(c.is_constant());
+
+            let sum = a.add(&mut cs, &b).unwrap();
+            assert_eq!(sum.get_value().unwrap(), (a_value.clone() + &b_value) % &modulus);
+
+            let difference = b.sub(&mut cs, &a).unwrap();
+            assert_eq!(difference.get_value().unwrap(), (modulus.clone() + &b_value - &a_value) % &modulus);
+
+            let product = a.mul(&mut cs, &c).unwrap();
+            assert_eq!(product.get_value().unwrap(), (a_value.clone() * &b_value) % &modulus);
+
+            let (low, high) = a.mul_wide(&mut cs, &b).unwrap();
+            assert_eq!(low.get_value().unwrap(), (a_value.clone() * &b_value) % &modulus);
+            assert_eq!(high.get_value().unwrap(), (a_value.clone() * &b_value) >> 256);
+
+            let (quotient, remainder) = a.div(&mut cs, &b).unwrap();
+            assert_eq!(quotient.get_value().unwrap(), a_value.clone() / &b_value);
+            assert_eq!(remainder.get_value().unwrap(), a_value % &b_value);
+        }
+
+        assert!(cs.is_satisfied());
+
+        let a = UInt256::alloc(&mut cs, Some(BigUint::from(1u64))).unwrap();
+        let zero = UInt256::alloc(&mut cs, Some(BigUint::from(0u64))).unwrap();
+        assert!(a.div(&mut cs, &zero).is_err());
+    }
+
+    #[test]
+    fn test_uint256_sub_checked() {
+        let mut cs = init_cs();
+
+        let modulus = BigUint::from(1u64) << 256;
+        let small_value = BigUint::from(0xdeadbeefu64) << 70;
+        let large_value = (BigUint::from(1u64) << 200) + 1u64;
+        let small = UInt256::alloc(&mut cs, Some(small_value.clone())).unwrap();
+        let large = UInt256::alloc(&mut cs, Some(large_value.clone())).unwrap();
+
+        let pairs = [(&small, &small, &small_value, &small_value), (&large, &small, &large_value, &small_value), (&small, &large, &small_value, &large_value)];
+        for &(a, b, a_value, b_value) in pairs.iter() {
+            let (difference, borrow) = a.sub_checked(&mut cs, b).unwrap();
+            assert_eq!(difference.get_value().unwrap(), (modulus.clone() + a_value - b_value) % &modulus);
+            assert_eq!(borrow.get_value().unwrap(), a_value < b_value);
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_checked_boundaries() {
+        let mut cs = init_cs();
+
+        let max_value = (BigUint::from(1u64) << 256) - 1u64;
+        let max = UInt256::alloc(&mut cs, Some(max_value.clone())).unwrap();
+        let one = UInt256::alloc(&mut cs, Some(BigUint::from(1u64))).unwrap();
+        let zero = UInt256::alloc(&mut cs, Some(BigUint::from(0u64))).unwrap();
+
+        // MAX + 1 wraps to zero with a carry, MAX + 0 doesn't carry
+        let (sum, carry) = max.add_checked(&mut cs, &one).unwrap();
+        assert!(sum.get_value().unwrap().is_zero());
+        assert!(carry.get_value().unwrap());
+        let (sum, carry) = max.add_checked(&mut cs, &zero).unwrap();
+        assert_eq!(sum.get_value().unwrap(), max_value);
+        assert!(!carry.get_value().unwrap());
+
+        // 0 - 1 wraps to MAX with a borrow, 1 - 1 doesn't borrow
+        let (difference, borrow) = zero.sub_checked(&mut cs, &one).unwrap();
+        assert_eq!(difference.get_value().unwrap(), max_value);
+        assert!(borrow.get_value().unwrap());
+        let (difference, borrow) = one.sub_checked(&mut cs, &one).unwrap();
+        assert!(difference.get_value().unwrap().is_zero());
+        assert!(!borrow.get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_is_zero() {
+        let mut cs = init_cs();
+
+        let zero = UInt256::alloc(&mut cs, Some(BigUint::from(0u64))).unwrap();
+        let nonzero = UInt256::alloc(&mut cs, Some(BigUint::from(1u64) << 200)).unwrap();
+        let constant = UInt256::<Bn256>::from_biguint(&BigUint::from(0u64));
+
+        assert!(zero.is_zero(&mut cs).unwrap().get_value().unwrap());
+        assert!(!nonzero.is_zero(&mut cs).unwrap().get_value().unwrap());
+        assert!(constant.is_zero(&mut cs).unwrap().get_constant_value());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_equals() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        let value = random_biguint(rng, 4);
+        let a = UInt256::alloc(&mut cs, Some(value.clone())).unwrap();
+        let b = UInt256::alloc(&mut cs, Some(value.clone())).unwrap();
+        let c = UInt256::alloc(&mut cs, Some(value.clone() ^ (BigUint::from(1u64) << 130))).unwrap();
+        let constant = UInt256::<Bn256>::from_biguint(&value);
+
+        assert!(a.equals(&mut cs, &b).unwrap().get_value().unwrap());
+        assert!(a.equals(&mut cs, &constant).unwrap().get_value().unwrap());
+        assert!(!a.equals(&mut cs, &c).unwrap().get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_less_than() {
+        let mut cs = init_cs();
+
+        let max_value = (BigUint::from(1u64) << 256) - 1u64;
+        let zero = UInt256::alloc(&mut cs, Some(BigUint::from(0u64))).unwrap();
+        let max = UInt256::alloc(&mut cs, Some(max_value)).unwrap();
+
+        assert!(zero.less_than(&mut cs, &max).unwrap().get_value().unwrap());
+        assert!(!max.less_than(&mut cs, &zero).unwrap().get_value().unwrap());
+        assert!(!max.less_than(&mut cs, &max).unwrap().get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_conditionally_select() {
+        let mut cs = init_cs();
+
+        let a_value = BigUint::from(1u64) << 200;
+        let b_value = BigUint::from(12345u64);
+        let a = UInt256::alloc(&mut cs, Some(a_value.clone())).unwrap();
+        let b = UInt256::alloc(&mut cs, Some(b_value.clone())).unwrap();
+
+        for &flag_value in [true, false].iter() {
+            let flag = Boolean::alloc(&mut cs, Some(flag_value)).unwrap();
+            let selected = UInt256::conditionally_select(&mut cs, &flag, &a, &b).unwrap();
+            let expected = if flag_value { &a_value } else { &b_value };
+            assert_eq!(&selected.get_value().unwrap(), expected);
+        }
+
+        let n = cs.get_current_step_number();
+        let selected = UInt256::conditionally_select(&mut cs, &Boolean::constant(false), &a, &b).unwrap();
+        assert_eq!(selected.get_value().unwrap(), b_value);
+        assert_eq!(cs.get_current_step_number(), n);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_bitwise_ops() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        let x_value = random_biguint(rng, 4);
+        let y_value = random_biguint(rng, 4);
+        let x = UInt256::alloc(&mut cs, Some(x_value.clone())).unwrap();
+        let y = UInt256::alloc(&mut cs, Some(y_value.clone())).unwrap();
+
+        assert_eq!(x.bitand(&mut cs, &y).unwrap().get_value().unwrap(), x_value.clone() & &y_value);
+        assert_eq!(x.bitor(&mut cs, &y).unwrap().get_value().unwrap(), x_value.clone() | &y_value);
+        assert_eq!(x.bitxor(&mut cs, &y).unwrap().get_value().unwrap(), x_value.clone() ^ &y_value);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_from_num() {
+        use crate::bellman::pairing::bn256::Fr;
+        use crate::bellman::pairing::ff::PrimeField;
+
+        let mut cs = init_cs();
+        let value = Fr::from_str("123456789123456789123456789123456789123456789").unwrap();
+        let num = Num::alloc(&mut cs, Some(value)).unwrap();
+
+        let uint = UInt256::from_num(&mut cs, &num).unwrap();
+        assert_eq!(uint.get_value().unwrap(), fe_to_biguint(&value));
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_enforce_equal_to_num() {
+        let mut cs = init_cs();
+
+        let value = BigUint::from(0xdeadbeefu64) << 100;
+        let uint = UInt256::alloc(&mut cs, Some(value.clone())).unwrap();
+        let num = Num::alloc(&mut cs, Some(biguint_to_fe(value))).unwrap();
+        uint.enforce_equal_to_num(&mut cs, &num).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_from_le_bytes() {
+        use crate::bellman::pairing::bn256::Fr;
+        use crate::plonk::circuit::utils::u64_to_fe;
+
+        let mut cs = init_cs();
+
+        let mut bytes = [Num::Constant(Fr::zero()); 32];
+        let mut expected = BigUint::from(0u64);
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let byte_value = (i as u64 * 37 + 11) % 256;
+            *byte = Num::alloc(&mut cs, Some(u64_to_fe(byte_value))).unwrap();
+            expected += BigUint::from(byte_value) << (8 * i);
+        }
+
+        let uint = UInt256::from_le_bytes(&mut cs, &bytes).unwrap();
+        assert_eq!(uint.get_value().unwrap(), expected);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_into_le_bytes() {
+        use crate::bellman::pairing::bn256::Fr;
+        use crate::plonk::circuit::utils::u64_to_fe;
+
+        let mut cs = init_cs();
+
+        let value = (BigUint::from(0x0123456789abcdefu64) << 190) + BigUint::from(0xfedcba98u64);
+        let uint = UInt256::alloc(&mut cs, Some(value.clone())).unwrap();
+        let bytes = uint.into_le_bytes(&mut cs).unwrap();
+
+        let mut expected = value.to_bytes_le();
+        expected.resize(32, 0u8);
+        for (byte, expected) in bytes.iter().zip(expected.into_iter()) {
+            assert_eq!(byte.get_value().unwrap(), u64_to_fe::<Fr>(expected as u64));
+        }
+
+        let recomposed = UInt256::from_le_bytes(&mut cs, &bytes).unwrap();
+        assert_eq!(recomposed.get_value().unwrap(), value);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_modmul() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x4dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        let modulus_value = (BigUint::from(1u64) << 255) - 19u64;
+        let a_value = random_biguint(rng, 4);
+        let b_value = random_biguint(rng, 4);
+        let a = UInt256::alloc(&mut cs, Some(a_value.clone())).unwrap();
+        let b = UInt256::alloc(&mut cs, Some(b_value.clone())).unwrap();
+        let modulus = UInt256::alloc(&mut cs, Some(modulus_value.clone())).unwrap();
+
+        let result = a.modmul(&mut cs, &b, &modulus).unwrap();
+        assert_eq!(result.get_value().unwrap(), (a_value * b_value) % &modulus_value);
+
+        assert!(cs.is_satisfied());
+
+        let zero = UInt256::alloc(&mut cs, Some(BigUint::from(0u64))).unwrap();
+        assert!(a.modmul(&mut cs, &b, &zero).is_err());
+    }
+
+    #[test]
+    fn test_uint256_modpow() {
+        use rand::{XorShiftRng, SeedableRng, Rng};
+        let rng = &mut XorShiftRng::from_seed([0x2dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        // 2^255 - 19
+        let modulus_value = (BigUint::from(1u64) << 255) - 19u64;
+        let base_value = random_biguint(rng, 4) % &modulus_value;
+        let exponent: u32 = rng.gen::<u32>() >> 16;
+
+        let base = UInt256::alloc(&mut cs, Some(base_value.clone())).unwrap();
+        let modulus = UInt256::<Bn256>::from_biguint(&modulus_value);
+        let exponent_bits: Vec<Boolean> = (0..16).map(|i| {
+            Boolean::alloc(&mut cs, Some((exponent >> i) & 1 == 1)).unwrap()
+        }).collect();
+
+        let result = base.modpow(&mut cs, &exponent_bits, &modulus).unwrap();
+        assert_eq!(result.get_value().unwrap(), base_value.modpow(&BigUint::from(exponent), &modulus_value));
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uintn_with_bit_width() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x6dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+
+        // address-like and 512-bit values
+        for &(bits, num_limbs, top_limb_width) in [(160usize, 3usize, 32usize), (512, 8, 64)].iter() {
+            let value = random_biguint(rng, num_limbs) >> (num_limbs * LIMB_WIDTH - bits);
+            let uint = GenericUInt::<Bn256>::with_bit_width(&mut cs, Some(value.clone()), bits).unwrap();
+            assert_eq!(uint.get_limbs().len(), num_limbs);
+            assert_eq!(uint.top_limb_width(), top_limb_width);
+            assert_eq!(uint.bit_width(), bits);
+            assert_eq!(uint.get_value().unwrap(), value);
+        }
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_uint256_to_bits_le() {
+        use rand::{XorShiftRng, SeedableRng};
+        let rng = &mut XorShiftRng::from_seed([0x7dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+
+        let mut cs = init_cs();
+        let value = random_biguint(rng, 4);
+        let uint = UInt256::alloc(&mut cs, Some(value.clone())).unwrap();
+
+        let bits = uint.to_bits_le(&mut cs).unwrap();
+        assert_eq!(bits.len(), 256);
+        let mut recomposed = BigUint::from(0u64);
+        for bit in bits.iter().rev() {
+            recomposed <<= 1;
+            recomposed += BigUint::from(bit.get_value().unwrap() as u64);
+        }
+        assert_eq!(recomposed, value);
+
+        let constant = UInt256::<Bn256>::from_biguint(&value);
+        let n = cs.get_current_step_number();
+        assert!(constant.to_bits_le(&mut cs).unwrap().iter().all(|el| el.is_constant()));
+        assert_eq!(cs.get_current_step_number(), n);
+
+        assert!(cs.is_satisfied());
+    }
+}
@@ -57,7 +57,9 @@ pub fn split_into_slices<F: PrimeField>(el: &F, slice_width: usize, num_slices:
         }
     }
     else {
-        let it = repr.as_ref().iter().map(|x| u64_to_fe::<F>(*x)).take(num_slices);
+        // slice may span several words of the representation, so go through BigUint
+        let it = split_into_fixed_number_of_limbs(fe_to_biguint(el), slice_width, num_slices).into_iter()
+            .map(|x| biguint_to_fe::<F>(x));
         slices.extend(it);
     };
 